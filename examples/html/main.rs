@@ -0,0 +1,67 @@
+use std::rc::Rc;
+
+use reust::frontend::html::*;
+use reust::prelude::*;
+
+// Static report, written to stdout:
+//     cargo run --example html > report.html
+fn main() {
+    let state = new_state_store();
+    let graph = render_app_to_graph(&app(), state);
+    print!("{}", render_document("Sales report", &graph));
+}
+
+fn app() -> El<HtmlNode> {
+    El::Container(vec![
+        El::Node(element("h1").add_child(text("Sales report"))),
+        report(vec![
+            ("North".to_string(), 1250.0),
+            ("South".to_string(), 830.5),
+            ("East & West".to_string(), 2040.0),
+        ]),
+    ])
+}
+
+fn report(rows: Vec<(String, f64)>) -> El<HtmlNode> {
+    El::Component(Box::new(Report { rows }))
+}
+
+pub struct Report {
+    pub rows: Vec<(String, f64)>,
+}
+
+impl Component<HtmlNode> for Report {
+    fn render(&self, _: Rc<BoxedState>, _: Rc<SetState>) -> El<HtmlNode> {
+        let total: f64 = self.rows.iter().map(|(_, v)| v).sum();
+        let row = |cells: Vec<El<HtmlNode>>, tag: &str| {
+            El::Node(
+                element("tr").add_children(
+                    cells
+                        .into_iter()
+                        .map(|c| El::Node(element(tag).add_child(c)))
+                        .collect(),
+                ),
+            )
+        };
+
+        let mut rows = vec![row(vec![text("Region"), text("Sales")], "th")];
+        rows.extend(
+            self.rows.iter().map(|(region, sales)| {
+                row(vec![text(region), text(&format!("{:.2}", sales))], "td")
+            }),
+        );
+        rows.push(row(
+            vec![text("Total"), text(&format!("{:.2}", total))],
+            "th",
+        ));
+
+        El::Node(
+            Node::new(
+                HtmlNode::new("table")
+                    .set_attribute("class", "report")
+                    .set_attribute("border", "1"),
+            )
+            .add_children(rows),
+        )
+    }
+}
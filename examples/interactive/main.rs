@@ -0,0 +1,120 @@
+use std::rc::Rc;
+use std::time::Duration;
+
+use reust::frontend::text::*;
+use reust::prelude::*;
+
+// Line mode UI, usable in dumb terminals or over pipes:
+//     printf 'click 1\ninput 3 Ada\nquit\n' | cargo run --example interactive
+// The state is kept across runs with: -- --state <file>
+fn main() {
+    let state = new_state_store();
+    let registry = registry();
+    let file = std::env::args().skip_while(|a| a != "--state").nth(1);
+
+    if let Some(snapshot) = file.as_ref().and_then(|f| StateSnapshot::load(f).ok()) {
+        state.borrow_mut().restore(&registry, &snapshot);
+    }
+    run_interactive(app, state.clone());
+    if let Some(file) = file {
+        if let Err(e) = state.borrow().snapshot(&registry).save(&file) {
+            eprintln!("Could not save the state to {}: {}", file, e);
+        }
+    }
+}
+
+// uptime is not kept
+fn registry() -> StateRegistry {
+    StateRegistry::new().register::<App, AppState>(
+        |s| format!("{}\n{}", s.count, s.name),
+        |data| {
+            let (count, name) = data.split_once('\n')?;
+            Some(AppState {
+                count: count.parse().ok()?,
+                name: name.to_string(),
+                uptime: 0,
+            })
+        },
+    )
+}
+
+fn app() -> El<TextNode> {
+    El::Component(Box::new(App {}))
+}
+
+#[derive(Clone)]
+pub struct AppState {
+    pub count: i32,
+    pub name: String,
+    pub uptime: u32,
+}
+
+pub struct App {}
+
+impl StateReceiver<AppState> for App {}
+impl Component<TextNode> for App {
+    fn initial_state(&self) -> Rc<dyn std::any::Any> {
+        Rc::new(AppState {
+            count: 0,
+            name: "nobody".to_string(),
+            uptime: 0,
+        })
+    }
+
+    fn render(&self, state: Rc<BoxedState>, set_state: Rc<SetState>) -> El<TextNode> {
+        let state = self.must_receive_state(state);
+
+        let on_increment = {
+            let (state, set_state) = (state.clone(), set_state.clone());
+            Rc::new(move || {
+                set_state(Rc::new(AppState {
+                    count: state.count + 1,
+                    ..state.clone()
+                }))
+            })
+        };
+        let on_reset = {
+            let (state, set_state) = (state.clone(), set_state.clone());
+            Rc::new(move || {
+                set_state(Rc::new(AppState {
+                    count: 0,
+                    ..state.clone()
+                }))
+            })
+        };
+        let on_name = {
+            let state = state.clone();
+            Rc::new(move |name: String| {
+                set_state(Rc::new(AppState {
+                    name,
+                    ..state.clone()
+                }))
+            })
+        };
+
+        El::Container(vec![
+            El::Node(
+                node("# Counter")
+                    .add_child(El::Node(node(&format!("The counter is {}", state.count))))
+                    .add_child(El::Node(Node::new(
+                        TextNode::new("Increment").set_on_click(Some(on_increment)),
+                    )))
+                    .add_child(El::Node(Node::new(
+                        TextNode::new("Reset").set_on_click(Some(on_reset)),
+                    ))),
+            ),
+            El::Node(
+                node(&format!("# Hello, {}", state.name)).add_child(El::Node(Node::new(
+                    TextNode::new("Name").set_on_input(Some(on_name)),
+                ))),
+            ),
+            El::Node(node(&format!("Up for {}s", state.uptime))),
+        ])
+    }
+
+    fn did_mount(&self, handle: &ComponentHandle) {
+        handle.set_interval(Duration::from_secs(10), |handle| {
+            handle.update_state(|state: &mut AppState| state.uptime += 10);
+        });
+    }
+}
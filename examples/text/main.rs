@@ -1,5 +1,6 @@
 use std::rc::Rc;
-use std::{thread::sleep, time::Duration};
+use std::thread::{sleep, spawn};
+use std::time::Duration;
 
 use reust::frontend::text::*;
 use reust::prelude::*;
@@ -9,9 +10,21 @@ use reust::prelude::*;
 
 fn main() {
     let state = new_state_store();
+
+    // the first render, as JSON and as a tree: cargo run --example text -- --dump
+    if std::env::args().any(|a| a == "--dump") {
+        let graph = render_app_to_graph(&app(), state);
+        println!("{}", to_json_pretty(&graph, &text_payload));
+        print!("{}", debug_dump(&graph, &text_payload));
+        return;
+    }
+
+    draw_graph(render_app_to_graph(&app(), state.clone()));
     loop {
-        draw_graph(render_app_to_graph(&app(), state.clone()));
-        sleep(Duration::from_millis(500));
+        wait_scheduled(&state, Duration::from_secs(1));
+        if run_scheduled(&state) {
+            draw_graph(render_app_to_graph(&app(), state.clone()));
+        }
     }
 }
 
@@ -22,6 +35,8 @@ fn app() -> El<TextNode> {
 #[derive(Clone)]
 pub struct AppState {
     pub value: i32,
+    pub status: String,
+    pub progress: u32,
 }
 
 pub struct App {
@@ -31,22 +46,55 @@ pub struct App {
 impl StateReceiver<AppState> for App {}
 impl Component<TextNode> for App {
     fn initial_state(&self) -> Rc<dyn std::any::Any> {
-        Rc::new(AppState { value: 0 })
+        Rc::new(AppState {
+            value: 0,
+            status: "Loading...".to_string(),
+            progress: 0,
+        })
     }
 
-    fn render(&self, state: Rc<BoxedState>, set_state: Rc<SetState>) -> El<TextNode> {
+    fn render(&self, state: Rc<BoxedState>, _: Rc<SetState>) -> El<TextNode> {
         let state = self.must_receive_state(state);
 
-        set_state(Rc::new(AppState {
-            value: state.value + self.increment,
-        }));
-
         El::Container(vec![
             El::Node(
                 node("# Header A")
-                    .add_child(El::Node(node(&format!("The counter is {}", state.value)))),
+                    .add_child(El::Node(node(&format!("The counter is {}", state.value))))
+                    .add_child(El::Node(node(&state.status)))
+                    .add_child(El::Node(node(&format!(
+                        "Worker progress: {}/5",
+                        state.progress
+                    )))),
             ),
             El::Node(node("Ctrl-c to quit")),
         ])
     }
+
+    fn did_mount(&self, handle: &ComponentHandle) {
+        let increment = self.increment;
+        handle.set_interval(Duration::from_millis(500), move |handle| {
+            handle.update_state(|state: &mut AppState| state.value += increment);
+        });
+
+        handle.spawn(
+            || {
+                // some slow work, off the UI thread
+                sleep(Duration::from_secs(2));
+                "Loaded in the background".to_string()
+            },
+            |handle, status| {
+                handle.update_state(|state: &mut AppState| state.status = status);
+            },
+        );
+
+        // a thread of our own, posting its updates to the UI thread
+        if let Some(sync_set_state) = handle.sync_set_state() {
+            spawn(move || {
+                for progress in 1..=5 {
+                    sleep(Duration::from_millis(300));
+                    sync_set_state.update(move |state: &mut AppState| state.progress = progress);
+                }
+            });
+        }
+    }
 }
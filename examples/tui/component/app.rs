@@ -1,15 +1,242 @@
+use std::rc::Rc;
+
 use reust::engine::*;
+use reust::frontend::tui::animation::*;
+use reust::frontend::tui::layout::*;
+use reust::frontend::tui::scroll::*;
+use reust::frontend::tui::widgets::*;
 use reust::frontend::tui::*;
 
-use crate::component::header::*;
+use crate::component::crash::crash;
+use crate::component::file_tree::FileTree;
+use crate::component::help::Help;
 use crate::component::settings_controls::SettingsControls;
 
-pub fn app() -> El<TUINode> {
+// Messages returned by the event handlers, applied by the update function of main
+pub enum AppMsg {
+    Quit,
+}
+
+pub fn app(clock: &FrameClock) -> El<TUINode> {
     El::Container(vec![
-        header(HeaderProps {
+        label(LabelProps {
             pos: Position { left: 1, top: 1 },
-            text: "Reactive TUI experiment with Rust".to_string(),
+            text: "# Reactive TUI experiment with Rust".to_string(),
+            ..Default::default()
+        }),
+        separator(SeparatorProps {
+            pos: Position { left: 1, top: 2 },
+            length: 100,
+            ..Default::default()
+        }),
+        checkbox(CheckboxProps {
+            pos: Position { left: 10, top: 4 },
+            label: "Checkbox".to_string(),
+            ..Default::default()
+        }),
+        toggle(ToggleProps {
+            pos: Position { left: 10, top: 6 },
+            label: "Toggle".to_string(),
+            on: true,
+            ..Default::default()
+        }),
+        radio_group(RadioGroupProps {
+            pos: Position { left: 45, top: 4 },
+            options: vec![
+                "Small".to_string(),
+                "Medium".to_string(),
+                "Large".to_string(),
+            ],
+            selected: 1,
+            ..Default::default()
+        }),
+        spinner(SpinnerProps {
+            pos: Position { left: 70, top: 4 },
+            label: "Working...".to_string(),
+            clock: Some(clock.clone()),
+            ..Default::default()
+        }),
+        El::Component(Box::new(SettingsControls {
+            increment: 10,
+            clock: clock.clone(),
+        })),
+        tabs(TabsProps {
+            pos: Position { left: 70, top: 8 },
+            dim: Dimension {
+                width: 30,
+                height: 10,
+            },
+            tabs: vec![
+                Tab::new("Lines", |pos, dim| {
+                    scroll_view(
+                        ScrollViewProps {
+                            pos,
+                            dim,
+                            content_height: 40,
+                            ..Default::default()
+                        },
+                        move || {
+                            El::Container(
+                                (0..40)
+                                    .map(|i| {
+                                        El::Node(Node::new(
+                                            TUINode::new(pos.left, pos.top + i).set_text(Some(
+                                                format!("Scrollable line #{}", i + 1),
+                                            )),
+                                        ))
+                                    })
+                                    .collect(),
+                            )
+                        },
+                    )
+                }),
+                Tab::new("Split", |pos, dim| {
+                    split_pane(SplitPaneProps {
+                        pos,
+                        dim,
+                        split: dim.width / 2,
+                        min_first: 5,
+                        min_second: 5,
+                        first: Box::new(|pos, _| {
+                            El::Node(Node::new(
+                                TUINode::new(pos.left, pos.top).set_text(Some("Drag".to_string())),
+                            ))
+                        }),
+                        second: Box::new(|pos, _| {
+                            El::Node(Node::new(
+                                TUINode::new(pos.left, pos.top)
+                                    .set_text(Some("the divider".to_string())),
+                            ))
+                        }),
+                        ..Default::default()
+                    })
+                }),
+                Tab::new("Bars", |pos, dim| {
+                    El::Container(vec![
+                        sparkline(SparklineProps {
+                            pos,
+                            width: dim.width,
+                            data: (0..60).map(|i| ((i as f64) / 4.0).sin() + 1.0).collect(),
+                            ..Default::default()
+                        }),
+                        bar_chart(BarChartProps {
+                            pos: Position {
+                                left: pos.left,
+                                top: pos.top + 1,
+                            },
+                            dim: Dimension {
+                                width: dim.width,
+                                height: dim.height - 1,
+                            },
+                            bars: vec![
+                                ("Mon".to_string(), 3.0),
+                                ("Tue".to_string(), 7.5),
+                                ("Wed".to_string(), 5.0),
+                                ("Thu".to_string(), 9.0),
+                                ("Fri".to_string(), 1.0),
+                            ],
+                            ..Default::default()
+                        }),
+                    ])
+                }),
+                Tab::new("Line", |pos, dim| {
+                    line_chart(LineChartProps {
+                        pos,
+                        dim,
+                        series: vec![
+                            (0..50).map(|i| ((i as f64) / 5.0).sin() * 10.0).collect(),
+                            (0..50).map(|i| ((i as f64) / 5.0).cos() * 5.0).collect(),
+                        ],
+                        x_labels: vec!["0s".to_string(), "25s".to_string(), "50s".to_string()],
+                        ..Default::default()
+                    })
+                }),
+            ],
+            ..Default::default()
+        }),
+        list(ListProps {
+            pos: Position { left: 70, top: 19 },
+            dim: Dimension {
+                width: 30,
+                height: 7,
+            },
+            items: Rc::new(
+                [
+                    "Apple",
+                    "Apricot",
+                    "Banana",
+                    "Blackberry",
+                    "Blueberry",
+                    "Cherry",
+                    "Coconut",
+                    "Fig",
+                    "Grape",
+                    "Kiwi",
+                    "Lemon",
+                    "Lime",
+                    "Mango",
+                    "Melon",
+                    "Orange",
+                    "Papaya",
+                    "Peach",
+                    "Pear",
+                    "Pineapple",
+                    "Plum",
+                    "Raspberry",
+                    "Strawberry",
+                ]
+                .iter()
+                .map(|f| f.to_string())
+                .collect(),
+            ),
+            multi_select: true,
+            ..Default::default()
+        }),
+        table(TableProps {
+            pos: Position { left: 10, top: 24 },
+            dim: Dimension {
+                width: 55,
+                height: 6,
+            },
+            columns: vec![
+                Column {
+                    title: "Job".to_string(),
+                    width: ColumnWidth::Fixed(8),
+                    ..Default::default()
+                },
+                Column {
+                    title: "Host".to_string(),
+                    ..Default::default()
+                },
+                Column {
+                    title: "CPU %".to_string(),
+                    width: ColumnWidth::Percent(20),
+                    align: Align::Right,
+                    ..Default::default()
+                },
+            ],
+            rows: Rc::new(
+                (0..1000)
+                    .map(|i| {
+                        vec![
+                            format!("#{}", i + 1),
+                            format!("node-{:02}", i % 17),
+                            format!("{:.1}", (i * 37 % 1000) as f64 / 10.0),
+                        ]
+                    })
+                    .collect(),
+            ),
+            ..Default::default()
+        }),
+        El::Component(Box::new(FileTree {})),
+        El::Component(Box::new(Help {})),
+        crash(Position { left: 10, top: 33 }, false),
+        crash(Position { left: 45, top: 33 }, true),
+        button(ButtonProps {
+            pos: Position { left: 45, top: 30 },
+            title: "Quit".to_string(),
+            on_click: Some(Rc::new(|_: &mut UIEvent| message(AppMsg::Quit))),
+            ..Default::default()
         }),
-        El::Component(Box::new(SettingsControls { increment: 10 })),
     ])
 }
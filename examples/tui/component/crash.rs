@@ -0,0 +1,65 @@
+use std::any::Any;
+use std::rc::Rc;
+
+use reust::engine::*;
+use reust::frontend::tui::widgets::*;
+use reust::frontend::tui::*;
+
+// Fails its render once clicked, by returning an error or by panicking: the
+// error boundary around it renders a message instead, and the rest of the app
+// keeps running
+pub struct Crash {
+    pub pos: Position,
+    pub panics: bool,
+}
+
+#[derive(Clone)]
+struct CrashState {
+    clicked: bool,
+}
+
+impl StateReceiver<CrashState> for Crash {}
+impl Component<TUINode> for Crash {
+    fn initial_state(&self) -> Rc<dyn Any> {
+        Rc::new(CrashState { clicked: false })
+    }
+
+    fn render(&self, state: Rc<BoxedState>, set_state: Rc<SetState>) -> El<TUINode> {
+        let state = self.must_receive_state(state);
+
+        match (state.clicked, self.panics) {
+            (false, _) => button(ButtonProps {
+                pos: self.pos,
+                title: if self.panics { "Panic" } else { "Fail" }.to_string(),
+                on_click: Some(handle_on_click(set_state)),
+                ..Default::default()
+            }),
+            (true, false) => Err("the Fail button was clicked").into(),
+            (true, true) => panic!("the Panic button was clicked"),
+        }
+    }
+}
+
+fn handle_on_click(set_state: Rc<SetState>) -> MouseHandler {
+    Rc::new(move |_: &mut UIEvent| {
+        set_state(Rc::new(CrashState { clicked: true }));
+        None
+    })
+}
+
+pub fn crash(pos: Position, panics: bool) -> El<TUINode> {
+    error_boundary(
+        El::Component(Box::new(Crash { pos, panics })),
+        move |error| {
+            label(LabelProps {
+                pos: Position {
+                    left: pos.left,
+                    top: pos.top + 1,
+                },
+                text: format!("Failed: {}", error.message),
+                ..Default::default()
+            })
+        },
+        None,
+    )
+}
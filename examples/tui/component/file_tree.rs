@@ -0,0 +1,66 @@
+use std::any::Any;
+use std::collections::HashSet;
+use std::rc::Rc;
+
+use reust::engine::*;
+use reust::frontend::tui::widgets::*;
+use reust::frontend::tui::*;
+
+// Tree whose directories are listed when first expanded
+pub struct FileTree {}
+
+#[derive(Clone)]
+struct FileTreeState {
+    loaded: HashSet<String>,
+}
+
+impl StateReceiver<FileTreeState> for FileTree {}
+impl Component<TUINode> for FileTree {
+    fn initial_state(&self) -> Rc<dyn Any> {
+        Rc::new(FileTreeState {
+            loaded: HashSet::new(),
+        })
+    }
+
+    fn render(&self, state: Rc<BoxedState>, set_state: Rc<SetState>) -> El<TUINode> {
+        let state = self.must_receive_state(state);
+
+        tree_view(TreeViewProps {
+            pos: Position { left: 70, top: 27 },
+            dim: Dimension {
+                width: 30,
+                height: 10,
+            },
+            items: Rc::new(vec![TreeItem::new("/", "/")
+                .add_child(directory(&state.loaded, "/src", "src"))
+                .add_child(directory(&state.loaded, "/examples", "examples"))
+                .add_child(TreeItem::new("/Cargo.toml", "Cargo.toml"))
+                .add_child(TreeItem::new("/README.md", "README.md"))]),
+            expanded: vec!["/".to_string()],
+            on_load: Some(handle_on_load(state, set_state)),
+            ..Default::default()
+        })
+    }
+}
+
+// Lazy directory, with fake content once loaded
+fn directory(loaded: &HashSet<String>, id: &str, label: &str) -> TreeItem {
+    let item = TreeItem::new(id, label).set_lazy(true);
+    if !loaded.contains(id) {
+        return item;
+    }
+    item.add_children(vec![
+        directory(loaded, &format!("{}/nested", id), "nested"),
+        TreeItem::new(&format!("{}/main.rs", id), "main.rs"),
+        TreeItem::new(&format!("{}/mod.rs", id), "mod.rs"),
+    ])
+}
+
+fn handle_on_load(state: FileTreeState, set_state: Rc<SetState>) -> ChangeHandler<String> {
+    Rc::new(move |id: String| {
+        let mut state = state.clone();
+        state.loaded.insert(id);
+        set_state(Rc::new(state));
+        None
+    })
+}
@@ -0,0 +1,78 @@
+use std::any::Any;
+use std::rc::Rc;
+
+use reust::engine::*;
+use reust::frontend::tui::overlay::*;
+use reust::frontend::tui::widgets::*;
+use reust::frontend::tui::*;
+
+pub struct Help {}
+
+#[derive(Clone)]
+struct HelpState {
+    open: bool,
+}
+
+impl StateReceiver<HelpState> for Help {}
+impl Component<TUINode> for Help {
+    fn initial_state(&self) -> Rc<dyn Any> {
+        Rc::new(HelpState { open: false })
+    }
+
+    fn render(&self, state: Rc<BoxedState>, set_state: Rc<SetState>) -> El<TUINode> {
+        let state = self.must_receive_state(state);
+
+        El::Container(vec![
+            button(ButtonProps {
+                pos: Position { left: 10, top: 30 },
+                title: "Help".to_string(),
+                on_click: Some(handle_set_open(set_state.clone(), true)),
+                ..Default::default()
+            }),
+            if state.open {
+                modal(
+                    ModalProps {
+                        pos: Position { left: 20, top: 8 },
+                        dim: Dimension {
+                            width: 50,
+                            height: 14,
+                        },
+                        title: "Help".to_string(),
+                        on_dismiss: Some(handle_dismiss(set_state.clone())),
+                    },
+                    El::Container(vec![
+                        El::Node(Node::new(TUINode::new(22, 11).set_text(Some(
+                            "Click the buttons to change the value.".to_string(),
+                        )))),
+                        El::Node(Node::new(
+                            TUINode::new(22, 12)
+                                .set_text(Some("Press q to quit, Esc to close.".to_string())),
+                        )),
+                        button(ButtonProps {
+                            pos: Position { left: 22, top: 15 },
+                            title: "Close".to_string(),
+                            on_click: Some(handle_set_open(set_state, false)),
+                            ..Default::default()
+                        }),
+                    ]),
+                )
+            } else {
+                El::None
+            },
+        ])
+    }
+}
+
+fn handle_set_open(set_state: Rc<SetState>, open: bool) -> MouseHandler {
+    Rc::new(move |_: &mut UIEvent| {
+        set_state(Rc::new(HelpState { open }));
+        None
+    })
+}
+
+fn handle_dismiss(set_state: Rc<SetState>) -> DismissHandler {
+    Rc::new(move || {
+        set_state(Rc::new(HelpState { open: false }));
+        None
+    })
+}
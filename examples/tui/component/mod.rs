@@ -1,5 +1,5 @@
 pub mod app;
-pub mod button;
-pub mod header;
-pub mod progress_bar;
+pub mod crash;
+pub mod file_tree;
+pub mod help;
 pub mod settings_controls;
@@ -1,15 +1,14 @@
 use std::any::Any;
 use std::rc::Rc;
-use std::sync::Mutex;
 
 use reust::engine::*;
+use reust::frontend::tui::animation::*;
+use reust::frontend::tui::widgets::*;
 use reust::frontend::tui::*;
 
-use crate::component::button::*;
-use crate::component::progress_bar::*;
-
 pub struct SettingsControls {
     pub increment: i32,
+    pub clock: FrameClock,
 }
 
 #[derive(Clone)]
@@ -17,6 +16,17 @@ struct SettingsControlsState {
     percent: i32,
 }
 
+// Shows the state in the time travel panel
+pub fn register_state(registry: StateRegistry) -> StateRegistry {
+    registry.register::<SettingsControls, SettingsControlsState>(
+        |s| format!("percent={}", s.percent),
+        |data| {
+            let percent = data.strip_prefix("percent=")?.parse().ok()?;
+            Some(SettingsControlsState { percent })
+        },
+    )
+}
+
 impl StateReceiver<SettingsControlsState> for SettingsControls {}
 impl Component<TUINode> for SettingsControls {
     fn initial_state(&self) -> Rc<dyn Any> {
@@ -30,26 +40,39 @@ impl Component<TUINode> for SettingsControls {
             button(ButtonProps {
                 pos: Position { left: 10, top: 10 },
                 title: "Less".to_string(),
-                disable: state.percent <= 0,
+                disabled: state.percent <= 0,
                 on_click: Some(handle_on_less(
                     state.clone(),
                     set_state.clone(),
                     self.increment,
                 )),
+                ..Default::default()
             }),
             button(ButtonProps {
                 pos: Position { left: 45, top: 10 },
                 title: "Moar!".to_string(),
-                disable: state.percent >= 100,
+                disabled: state.percent >= 100,
                 on_click: Some(handle_on_more(
                     state.clone(),
                     set_state.clone(),
                     self.increment,
                 )),
+                ..Default::default()
             }),
-            progress_bar(ProgressBarProps {
-                pos: Position { left: 10, top: 20 },
-                percent: state.percent,
+            animated(AnimatedProps {
+                clock: self.clock.clone(),
+                value: state.percent.into(),
+                easing: Easing::EaseOutCubic,
+                content: Box::new(|percent| {
+                    progress_bar(ProgressBarProps {
+                        pos: Position { left: 10, top: 20 },
+                        width: 44,
+                        percent,
+                        border: true,
+                        ..Default::default()
+                    })
+                }),
+                ..Default::default()
             }),
             match state.percent {
                 x if x <= 0 => El::Node(Node::new(
@@ -68,8 +91,8 @@ fn handle_on_less(
     state: Rc<SettingsControlsState>,
     set_state: Rc<SetState>,
     increment: i32,
-) -> MouseClickHandler {
-    Rc::new(Mutex::new(move || {
+) -> MouseHandler {
+    Rc::new(move |_: &mut UIEvent| {
         set_state(Rc::new(SettingsControlsState {
             percent: if state.percent > increment {
                 state.percent - increment
@@ -77,15 +100,16 @@ fn handle_on_less(
                 0
             },
         }));
-    }))
+        None
+    })
 }
 
 fn handle_on_more(
     state: Rc<SettingsControlsState>,
     set_state: Rc<SetState>,
     increment: i32,
-) -> MouseClickHandler {
-    Rc::new(Mutex::new(move || {
+) -> MouseHandler {
+    Rc::new(move |_: &mut UIEvent| {
         set_state(Rc::new(SettingsControlsState {
             percent: if state.percent + increment > 100 {
                 100
@@ -93,5 +117,6 @@ fn handle_on_more(
                 state.percent + increment
             },
         }));
-    }))
+        None
+    })
 }
@@ -1,35 +1,111 @@
 use std::io::stdout;
+use std::rc::Rc;
 use std::time::Duration;
 
+use reust::frontend::tui::inspector::*;
+use reust::frontend::tui::metrics::*;
+use reust::frontend::tui::runtime::Runtime;
+use reust::frontend::tui::time_travel::*;
 use reust::frontend::tui::*;
 use reust::prelude::*;
 
 mod component;
-use component::app::app;
+use component::app::{app, AppMsg};
+use component::settings_controls;
 
-use termion::async_stdin;
-use termion::input::{MouseTerminal, TermRead};
+use termion::input::MouseTerminal;
 use termion::raw::IntoRawMode;
 
+struct Model {
+    running: bool,
+}
+
+fn update(mut model: Model, msg: AppMsg) -> Model {
+    match msg {
+        AppMsg::Quit => model.running = false,
+    }
+    model
+}
+
 fn main() {
     let mut stdout = MouseTerminal::from(stdout().into_raw_mode().unwrap());
-    let stdin = async_stdin();
-    let mut events_it = stdin.events();
-
-    let mut vsync = VSync::new(Duration::from_millis(16));
+    enable_mouse_motion(&mut stdout);
 
     let state = new_state_store();
+    // browsed in the time travel panel, toggled with t; i toggles the inspector
+    state.borrow_mut().enable_history(500);
+    let registry = Rc::new(settings_controls::register_state(StateRegistry::new()));
+    // frame metrics drawn with: cargo run --example tui -- --hud
+    let show_hud = std::env::args().any(|a| a == "--hud");
+    if show_hud {
+        state.borrow_mut().enable_metrics();
+    }
+    // at most 60 frames per second
+    let mut runtime = Runtime::new(state.clone(), Duration::from_millis(16)).unwrap();
+    let clock = runtime.clock();
+    let mut profiler = Profiler::new(runtime.frame_interval());
+
     let mut current_graph = None;
+    let mut input = InputState::new();
+    let mut model = Model { running: true };
 
-    loop {
-        if process_events(&mut events_it, &current_graph) {
+    // until stdin is closed
+    while let Ok(events) = runtime.wait() {
+        if process_events(&mut events.into_iter(), &current_graph, &mut input) {
             break;
         }
 
-        let graph = render_app_to_graph(&app(), state.clone());
-        draw_graph(&mut stdout, &graph);
-        current_graph = Some(graph);
+        run_scheduled(&state);
 
-        vsync.wait();
+        for msg in input.take_messages::<AppMsg>() {
+            model = update(model, msg);
+        }
+
+        if !model.running {
+            break;
+        }
+
+        // rendered again only on state changes, resizes and for running animations
+        if runtime.needs_render() {
+            let inspection = current_graph
+                .as_ref()
+                .map(|g| inspect_graph(g, input.mouse()))
+                .unwrap_or_default();
+            let root = El::Container(vec![
+                app(&clock),
+                inspector(InspectorProps {
+                    pos: Position { left: 102, top: 1 },
+                    dim: Dimension {
+                        width: 50,
+                        height: 30,
+                    },
+                    inspection,
+                    registry: Some(registry.clone()),
+                    ..InspectorProps::new(state.clone())
+                }),
+                time_travel(TimeTravelProps {
+                    pos: Position { left: 20, top: 12 },
+                    registry: Some(registry.clone()),
+                    ..TimeTravelProps::new(state.clone())
+                }),
+            ]);
+            let root = match show_hud {
+                false => root,
+                true => El::Container(vec![
+                    root,
+                    hud(HudProps {
+                        pos: Position { left: 70, top: 31 },
+                        metrics: profiler.metrics().clone(),
+                        ..Default::default()
+                    }),
+                ]),
+            };
+            let graph = render_app_to_graph(&root, state.clone());
+            let draw = draw_graph_measured(&mut stdout, &graph);
+            profiler.record(state.borrow().render_metrics(), draw);
+            current_graph = Some(graph);
+        }
     }
+
+    disable_mouse_motion(&mut stdout);
 }
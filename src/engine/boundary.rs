@@ -0,0 +1,354 @@
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::marker::PhantomData;
+use std::panic::{self, AssertUnwindSafe};
+use std::rc::Rc;
+use std::sync::Once;
+
+use crate::engine::*;
+
+// Error of the render of an element: a panic, or an El::Error
+#[derive(Clone, Debug)]
+pub struct RenderError {
+    pub message: String,
+    // innermost component being rendered, or the element the error was returned at
+    pub path: String,
+}
+
+impl fmt::Display for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (at {})", self.message, self.path)
+    }
+}
+
+impl std::error::Error for RenderError {}
+
+// Renders an El::Error for a failed result: render can return
+//     self.load().map(|data| ..).into()
+impl<TPayload, E: fmt::Display> From<Result<El<TPayload>, E>> for El<TPayload> {
+    fn from(result: Result<El<TPayload>, E>) -> Self {
+        match result {
+            Ok(el) => el,
+            Err(e) => El::Error(e.to_string()),
+        }
+    }
+}
+
+pub type ErrorHandler = Rc<dyn Fn(&RenderError)>;
+pub type Fallback<TPayload> = Box<dyn Fn(&RenderError) -> El<TPayload>>;
+
+pub struct ErrorBoundary<TPayload> {
+    pub content: El<TPayload>,
+    // rendered instead of the content when its render fails
+    pub fallback: Fallback<TPayload>,
+    pub on_error: Option<ErrorHandler>,
+}
+
+// Renders content, or the fallback when the render of content panics or
+// reaches an El::Error. Errors not caught by a boundary panic the app.
+pub fn error_boundary<TPayload>(
+    content: El<TPayload>,
+    fallback: impl Fn(&RenderError) -> El<TPayload> + 'static,
+    on_error: Option<ErrorHandler>,
+) -> El<TPayload> {
+    El::ErrorBoundary(Box::new(ErrorBoundary {
+        content,
+        fallback: Box::new(fallback),
+        on_error,
+    }))
+}
+
+type PanicCleanupFn = Rc<dyn Fn()>;
+
+thread_local! {
+    // error boundaries being rendered
+    static BOUNDARIES: Cell<usize> = const { Cell::new(0) };
+    // paths of the components being rendered, innermost last
+    static RENDERING: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    // run on the panics not caught by an error boundary, by id
+    static PANIC_CLEANUPS: RefCell<Vec<(u64, PanicCleanupFn)>> = const { RefCell::new(Vec::new()) };
+    static NEXT_CLEANUP_ID: Cell<u64> = const { Cell::new(0) };
+}
+
+// Whether a panic on this thread would be caught by an error boundary. While a
+// boundary renders, every panic of the thread is silenced, including the ones
+// its components catch themselves with catch_unwind: those are not reported.
+pub fn in_error_boundary() -> bool {
+    BOUNDARIES.with(|b| b.get() > 0)
+}
+
+pub(crate) fn enter_component(path: &str) {
+    RENDERING.with(|r| r.borrow_mut().push(String::from(path)));
+}
+
+pub(crate) fn leave_component() {
+    RENDERING.with(|r| r.borrow_mut().pop());
+}
+
+// The one hook of the engine, installed once, wrapping the previous one. Its
+// state is per thread: panics caught by the error boundaries of the thread are
+// not reported, the boundaries handle them; the others run the cleanups of the
+// thread, then are reported by the previous hook. The hook cannot tell which
+// panics a boundary will catch: see in_error_boundary.
+fn install_panic_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if BOUNDARIES.try_with(|b| b.get() > 0).unwrap_or(false) {
+                return;
+            }
+            let cleanups: Vec<PanicCleanupFn> = PANIC_CLEANUPS
+                .try_with(|c| match c.try_borrow() {
+                    Ok(c) => c.iter().map(|(_, f)| Rc::clone(f)).collect(),
+                    Err(_) => Vec::new(),
+                })
+                .unwrap_or_default();
+            // innermost first
+            for cleanup in cleanups.iter().rev() {
+                cleanup();
+            }
+            previous(info);
+        }));
+    });
+}
+
+// Registered by on_uncaught_panic; dropping it unregisters the cleanup
+pub struct PanicCleanup {
+    id: u64,
+    // the cleanup belongs to the thread that registered it
+    _thread_bound: PhantomData<Rc<()>>,
+}
+
+impl Drop for PanicCleanup {
+    fn drop(&mut self) {
+        let id = self.id;
+        let _ = PANIC_CLEANUPS.try_with(|c| c.borrow_mut().retain(|(i, _)| *i != id));
+    }
+}
+
+// Runs cleanup before the report of the panics of this thread not caught by an
+// error boundary, while the returned guard lives: e.g. to restore the terminal
+pub fn on_uncaught_panic(cleanup: impl Fn() + 'static) -> PanicCleanup {
+    install_panic_hook();
+    let id = NEXT_CLEANUP_ID.with(|n| {
+        n.set(n.get() + 1);
+        n.get()
+    });
+    PANIC_CLEANUPS.with(|c| c.borrow_mut().push((id, Rc::new(cleanup))));
+    PanicCleanup {
+        id,
+        _thread_bound: PhantomData,
+    }
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(s) => *s,
+        Err(payload) => match payload.downcast::<&str>() {
+            Ok(s) => s.to_string(),
+            Err(_) => "render panicked".to_string(),
+        },
+    }
+}
+
+pub(crate) fn render_error_boundary<TPayload: 'static>(
+    boundary: &ErrorBoundary<TPayload>,
+    path: String,
+    state_store: Rc<RefCell<StateStore>>,
+) -> RenderedEl<TPayload> {
+    install_panic_hook();
+    let depth = RENDERING.with(|r| r.borrow().len());
+
+    BOUNDARIES.with(|b| b.set(b.get() + 1));
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        render(&boundary.content, path.clone(), 0, Rc::clone(&state_store))
+    }));
+    BOUNDARIES.with(|b| b.set(b.get() - 1));
+
+    let payload = match result {
+        Ok(rendered) => return rendered,
+        Err(payload) => payload,
+    };
+
+    // the components left by the panic
+    let failed = RENDERING.with(|r| {
+        let mut r = r.borrow_mut();
+        let failed = r.get(depth..).and_then(|f| f.last().cloned());
+        r.truncate(depth);
+        failed
+    });
+    let error = match payload.downcast::<RenderError>() {
+        Ok(e) => *e,
+        Err(payload) => RenderError {
+            message: panic_message(payload),
+            path: failed.unwrap_or_else(|| path.clone()),
+        },
+    };
+
+    if let Some(on_error) = &boundary.on_error {
+        on_error(&error);
+    }
+    // apart from the paths of the content, for the states of the fallback
+    render(&(boundary.fallback)(&error), path, 1, state_store)
+}
+
+pub(crate) fn raise_render_error(message: &str, path: &str) -> ! {
+    let error = RenderError {
+        message: String::from(message),
+        path: String::from(path),
+    };
+    if in_error_boundary() {
+        panic::panic_any(error)
+    }
+    // printed by the panic hook
+    panic!("{}", error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+
+    struct Failing {
+        panics: bool,
+    }
+
+    impl Component<String> for Failing {
+        fn render(&self, _: Rc<BoxedState>, _: Rc<SetState>) -> El<String> {
+            if self.panics {
+                panic!("render failed");
+            }
+            Err("no data").into()
+        }
+    }
+
+    fn guarded(content: El<String>, errors: Rc<RefCell<Vec<String>>>) -> El<String> {
+        error_boundary(
+            content,
+            |error| El::Node(Node::new(format!("fallback: {}", error.message))),
+            Some(Rc::new(move |error: &RenderError| {
+                errors.borrow_mut().push(error.to_string())
+            })),
+        )
+    }
+
+    fn payload(graph: &RenderedEl<String>) -> Option<String> {
+        match graph {
+            RenderedEl::Node(n) => Some((*n.payload).clone()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn renders_the_content_when_it_does_not_fail() {
+        let errors = Rc::new(RefCell::new(Vec::new()));
+        let el = guarded(El::Node(Node::new("ok".to_string())), errors.clone());
+        let graph = render_app_to_graph(&el, new_state_store());
+        assert_eq!(payload(&graph), Some("ok".to_string()));
+        assert!(errors.borrow().is_empty());
+    }
+
+    #[test]
+    fn renders_the_fallback_on_panics_and_errors() {
+        for panics in [true, false] {
+            let errors = Rc::new(RefCell::new(Vec::new()));
+            let el = guarded(El::Component(Box::new(Failing { panics })), errors.clone());
+            let store = new_state_store();
+            // and again on the next renders
+            for _ in 0..2 {
+                let graph = render_app_to_graph(&el, store.clone());
+                let message = if panics { "render failed" } else { "no data" };
+                assert_eq!(payload(&graph), Some(format!("fallback: {}", message)));
+            }
+            assert_eq!(errors.borrow().len(), 2);
+            assert!(errors.borrow()[0].contains("Failing"));
+            assert!(!in_error_boundary());
+        }
+    }
+
+    #[test]
+    fn errors_out_of_a_boundary_panic() {
+        let el: El<String> = El::Component(Box::new(Failing { panics: false }));
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            render_app_to_graph(&el, new_state_store())
+        }));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn cleanups_run_on_uncaught_panics_only() {
+        let runs = Rc::new(Cell::new(0));
+        let counter = runs.clone();
+        let guard = on_uncaught_panic(move || counter.set(counter.get() + 1));
+
+        // caught by a boundary
+        let errors = Rc::new(RefCell::new(Vec::new()));
+        let el = guarded(El::Component(Box::new(Failing { panics: true })), errors);
+        render_app_to_graph(&el, new_state_store());
+        assert_eq!(runs.get(), 0);
+
+        // unwinding out of the app
+        let _ = panic::catch_unwind(|| panic!("uncaught"));
+        assert_eq!(runs.get(), 1);
+
+        drop(guard);
+        let _ = panic::catch_unwind(|| panic!("uncaught"));
+        assert_eq!(runs.get(), 1);
+    }
+
+    #[test]
+    fn cleanups_run_innermost_first_until_dropped() {
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let log = |name: &'static str| {
+            let calls = calls.clone();
+            move || calls.borrow_mut().push(name)
+        };
+        let outer = on_uncaught_panic(log("outer"));
+        let inner = on_uncaught_panic(log("inner"));
+
+        let _ = panic::catch_unwind(|| panic!("uncaught"));
+        assert_eq!(*calls.borrow(), ["inner", "outer"]);
+
+        drop(inner);
+        let _ = panic::catch_unwind(|| panic!("uncaught"));
+        assert_eq!(*calls.borrow(), ["inner", "outer", "outer"]);
+        drop(outer);
+    }
+
+    #[test]
+    fn other_threads_are_not_silenced_by_a_boundary() {
+        struct SpawnsPanic {
+            cleaned_up: Arc<AtomicBool>,
+        }
+
+        impl Component<String> for SpawnsPanic {
+            fn render(&self, _: Rc<BoxedState>, _: Rc<SetState>) -> El<String> {
+                assert!(in_error_boundary());
+                let cleaned_up = self.cleaned_up.clone();
+                let worker = thread::spawn(move || {
+                    assert!(!in_error_boundary());
+                    let _guard =
+                        on_uncaught_panic(move || cleaned_up.store(true, Ordering::SeqCst));
+                    panic!("worker failed");
+                });
+                assert!(worker.join().is_err());
+                El::None
+            }
+        }
+
+        let cleaned_up = Arc::new(AtomicBool::new(false));
+        let el = guarded(
+            El::Component(Box::new(SpawnsPanic {
+                cleaned_up: cleaned_up.clone(),
+            })),
+            Rc::new(RefCell::new(Vec::new())),
+        );
+        render_app_to_graph(&el, new_state_store());
+        // the worker panic went through the hook as an uncaught one
+        assert!(cleaned_up.load(Ordering::SeqCst));
+    }
+}
@@ -0,0 +1,317 @@
+use std::fmt::Write;
+
+use crate::engine::*;
+
+// Value a payload is serialized to, written as JSON
+#[derive(Clone, Debug, PartialEq)]
+pub enum DumpValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    List(Vec<DumpValue>),
+    // fields are written in order, for stable snapshots
+    Object(Vec<(String, DumpValue)>),
+}
+
+impl DumpValue {
+    pub fn object(fields: Vec<(&str, DumpValue)>) -> Self {
+        DumpValue::Object(
+            fields
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v))
+                .collect(),
+        )
+    }
+
+    pub fn to_json(&self) -> String {
+        let mut out = String::new();
+        write_json(&mut out, self, None, 0);
+        out
+    }
+
+    // Indented by two spaces per level, for readable diffs
+    pub fn to_json_pretty(&self) -> String {
+        let mut out = String::new();
+        write_json(&mut out, self, Some(2), 0);
+        out
+    }
+}
+
+impl From<bool> for DumpValue {
+    fn from(b: bool) -> Self {
+        DumpValue::Bool(b)
+    }
+}
+
+impl From<&str> for DumpValue {
+    fn from(s: &str) -> Self {
+        DumpValue::String(s.to_string())
+    }
+}
+
+impl From<String> for DumpValue {
+    fn from(s: String) -> Self {
+        DumpValue::String(s)
+    }
+}
+
+impl<T: Into<DumpValue>> From<Option<T>> for DumpValue {
+    fn from(o: Option<T>) -> Self {
+        o.map_or(DumpValue::Null, Into::into)
+    }
+}
+
+macro_rules! dump_number {
+    ($($t:ty),*) => {
+        $(impl From<$t> for DumpValue {
+            fn from(n: $t) -> Self {
+                DumpValue::Number(n as f64)
+            }
+        })*
+    };
+}
+dump_number!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize, f32, f64);
+
+// Implemented by the user for the payloads of a frontend; any closure taking a
+// payload and returning a DumpValue is one
+pub trait PayloadSerializer<TPayload> {
+    fn serialize(&self, payload: &TPayload) -> DumpValue;
+}
+
+impl<TPayload, F> PayloadSerializer<TPayload> for F
+where
+    F: Fn(&TPayload) -> DumpValue,
+{
+    fn serialize(&self, payload: &TPayload) -> DumpValue {
+        self(payload)
+    }
+}
+
+// Rendered nodes as {"type": "node", "path", "payload", "children"},
+// containers as {"type": "container", "children"}, and None as null
+pub fn dump_value<TPayload>(
+    e: &RenderedEl<TPayload>,
+    serializer: &impl PayloadSerializer<TPayload>,
+) -> DumpValue {
+    let children = |cn: &Vec<RenderedEl<TPayload>>| {
+        DumpValue::List(cn.iter().map(|c| dump_value(c, serializer)).collect())
+    };
+    match e {
+        RenderedEl::None => DumpValue::Null,
+        RenderedEl::Node(rel) => DumpValue::object(vec![
+            ("type", "node".into()),
+            ("path", rel.path.as_str().into()),
+            ("payload", serializer.serialize(&rel.payload)),
+            ("children", children(&rel.children)),
+        ]),
+        RenderedEl::Container(cont) => DumpValue::object(vec![
+            ("type", "container".into()),
+            ("children", children(cont)),
+        ]),
+    }
+}
+
+pub fn to_json<TPayload>(
+    e: &RenderedEl<TPayload>,
+    serializer: &impl PayloadSerializer<TPayload>,
+) -> String {
+    dump_value(e, serializer).to_json()
+}
+
+pub fn to_json_pretty<TPayload>(
+    e: &RenderedEl<TPayload>,
+    serializer: &impl PayloadSerializer<TPayload>,
+) -> String {
+    dump_value(e, serializer).to_json_pretty()
+}
+
+// One line per element, children indented under their parent:
+//     node /0~app::Counter/0~Node {"text":"Hello"}
+//         container
+//             none
+pub fn debug_dump<TPayload>(
+    e: &RenderedEl<TPayload>,
+    serializer: &impl PayloadSerializer<TPayload>,
+) -> String {
+    fn walk<TPayload>(
+        out: &mut String,
+        e: &RenderedEl<TPayload>,
+        serializer: &impl PayloadSerializer<TPayload>,
+        level: usize,
+    ) {
+        let indent = "    ".repeat(level);
+        match e {
+            RenderedEl::None => {
+                let _ = writeln!(out, "{}none", indent);
+            }
+            RenderedEl::Node(rel) => {
+                let payload = serializer.serialize(&rel.payload).to_json();
+                let _ = writeln!(out, "{}node {} {}", indent, rel.path, payload);
+                rel.children
+                    .iter()
+                    .for_each(|c| walk(out, c, serializer, level + 1));
+            }
+            RenderedEl::Container(cont) => {
+                let _ = writeln!(out, "{}container", indent);
+                cont.iter()
+                    .for_each(|c| walk(out, c, serializer, level + 1));
+            }
+        }
+    }
+
+    let mut out = String::new();
+    walk(&mut out, e, serializer, 0);
+    out
+}
+
+fn write_json(out: &mut String, value: &DumpValue, indent: Option<usize>, level: usize) {
+    // separator before the items of a list or an object, and before its end
+    let newline = |out: &mut String, level: usize| {
+        if let Some(n) = indent {
+            out.push('\n');
+            out.push_str(&" ".repeat(n * level));
+        }
+    };
+
+    match value {
+        DumpValue::Null => out.push_str("null"),
+        DumpValue::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        // JSON has no NaN nor infinity
+        DumpValue::Number(n) if !n.is_finite() => out.push_str("null"),
+        DumpValue::Number(n) if n.fract() == 0.0 && n.abs() < 1e15 => {
+            let _ = write!(out, "{}", *n as i64);
+        }
+        DumpValue::Number(n) => {
+            let _ = write!(out, "{}", n);
+        }
+        DumpValue::String(s) => write_json_string(out, s),
+        DumpValue::List(items) if items.is_empty() => out.push_str("[]"),
+        DumpValue::List(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                newline(out, level + 1);
+                write_json(out, item, indent, level + 1);
+            }
+            newline(out, level);
+            out.push(']');
+        }
+        DumpValue::Object(fields) if fields.is_empty() => out.push_str("{}"),
+        DumpValue::Object(fields) => {
+            out.push('{');
+            for (i, (key, item)) in fields.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                newline(out, level + 1);
+                write_json_string(out, key);
+                out.push_str(if indent.is_some() { ": " } else { ":" });
+                write_json(out, item, indent, level + 1);
+            }
+            newline(out, level);
+            out.push('}');
+        }
+    }
+}
+
+fn write_json_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(node: &&'static str) -> DumpValue {
+        DumpValue::object(vec![("text", (*node).into())])
+    }
+
+    fn graph() -> RenderedEl<&'static str> {
+        let el = El::Node(Node::new("root").add_children(vec![
+            El::Container(vec![El::Node(Node::new("a")), El::None]),
+            El::None,
+        ]));
+        render_app_to_graph(&el, new_state_store())
+    }
+
+    #[test]
+    fn escapes_strings() {
+        let value = DumpValue::from("quote \" backslash \\ \n\r\t \u{1} \u{1f} \u{7f} é");
+        assert_eq!(
+            value.to_json(),
+            "\"quote \\\" backslash \\\\ \\n\\r\\t \\u0001 \\u001f \u{7f} é\""
+        );
+        let key = DumpValue::object(vec![("a\"b", DumpValue::Null)]);
+        assert_eq!(key.to_json(), "{\"a\\\"b\":null}");
+    }
+
+    #[test]
+    fn writes_numbers() {
+        let value = DumpValue::List(vec![
+            3.into(),
+            (-2.5).into(),
+            f64::NAN.into(),
+            f64::INFINITY.into(),
+            1e20.into(),
+        ]);
+        assert_eq!(value.to_json(), "[3,-2.5,null,null,100000000000000000000]");
+    }
+
+    #[test]
+    fn pretty_format() {
+        let value = DumpValue::object(vec![
+            ("list", DumpValue::List(vec![1.into(), true.into()])),
+            ("empty", DumpValue::List(vec![])),
+            (
+                "object",
+                DumpValue::object(vec![("k", None::<bool>.into())]),
+            ),
+        ]);
+        assert_eq!(
+            value.to_json_pretty(),
+            "{\n  \"list\": [\n    1,\n    true\n  ],\n  \"empty\": [],\n  \"object\": {\n    \"k\": null\n  }\n}"
+        );
+    }
+
+    #[test]
+    fn dumps_nodes_containers_and_none() {
+        let graph = graph();
+        assert_eq!(
+            to_json(&graph, &text),
+            concat!(
+                "{\"type\":\"node\",\"path\":\"/0~Node\",\"payload\":{\"text\":\"root\"},\"children\":[",
+                "{\"type\":\"container\",\"children\":[",
+                "{\"type\":\"node\",\"path\":\"/0~Node/0~Container/0~Node\",\"payload\":{\"text\":\"a\"},\"children\":[]},",
+                "null]},",
+                "null]}"
+            )
+        );
+        assert_eq!(
+            debug_dump(&graph, &text),
+            concat!(
+                "node /0~Node {\"text\":\"root\"}\n",
+                "    container\n",
+                "        node /0~Node/0~Container/0~Node {\"text\":\"a\"}\n",
+                "        none\n",
+                "    none\n"
+            )
+        );
+    }
+}
@@ -0,0 +1,231 @@
+use std::any::Any;
+use std::collections::VecDeque;
+use std::rc::Rc;
+use std::time::Instant;
+
+use crate::engine::*;
+
+// A state set on the store; initial states are not recorded
+#[derive(Clone, Debug)]
+pub struct StateChange {
+    pub path: String,
+    // None: the component had no state yet
+    pub old: Option<Rc<dyn Any>>,
+    pub new: Rc<dyn Any>,
+    pub at: Instant,
+}
+
+// The last changes of the states, and how many of them are applied: less than
+// all of them while travelling back in time
+#[derive(Debug)]
+pub struct StateHistory {
+    changes: VecDeque<StateChange>,
+    capacity: usize,
+    position: usize,
+}
+
+impl StateHistory {
+    pub fn new(capacity: usize) -> Self {
+        StateHistory {
+            changes: VecDeque::new(),
+            capacity: capacity.max(1),
+            position: 0,
+        }
+    }
+
+    // A change made in the past drops the changes that followed it
+    fn record(&mut self, change: StateChange) {
+        self.changes.truncate(self.position);
+        self.changes.push_back(change);
+        if self.changes.len() > self.capacity {
+            self.changes.pop_front();
+        }
+        self.position = self.changes.len();
+    }
+
+    // Oldest first
+    pub fn changes(&self) -> impl Iterator<Item = &StateChange> {
+        self.changes.iter()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&StateChange> {
+        self.changes.get(index)
+    }
+
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    // Number of changes applied to the states
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn is_at_present(&self) -> bool {
+        self.position == self.changes.len()
+    }
+}
+
+impl StateStore {
+    // Records the next states set, keeping the last capacity changes
+    pub fn enable_history(&mut self, capacity: usize) {
+        if self.history.is_none() {
+            self.history = Some(StateHistory::new(capacity));
+        }
+    }
+
+    pub fn disable_history(&mut self) {
+        self.history = None;
+    }
+
+    pub fn history(&self) -> Option<&StateHistory> {
+        self.history.as_ref()
+    }
+
+    pub(crate) fn record_change(&mut self, change: StateChange) {
+        if let Some(h) = &mut self.history {
+            h.record(change);
+        }
+    }
+
+    // Undoes the last change applied; the app is to be rendered again
+    pub fn history_back(&mut self) -> bool {
+        let change = match &self.history {
+            Some(h) if h.position > 0 => h.changes[h.position - 1].clone(),
+            _ => return false,
+        };
+        self.apply(&change.path, change.old);
+        if let Some(h) = &mut self.history {
+            h.position -= 1;
+        }
+        true
+    }
+
+    // Applies again the first change undone
+    pub fn history_forward(&mut self) -> bool {
+        let change = match &self.history {
+            Some(h) if !h.is_at_present() => h.changes[h.position].clone(),
+            _ => return false,
+        };
+        self.apply(&change.path, Some(change.new));
+        if let Some(h) = &mut self.history {
+            h.position += 1;
+        }
+        true
+    }
+
+    // Moves to the states after the first position changes
+    pub fn history_go(&mut self, position: usize) -> bool {
+        let current = match &self.history {
+            Some(h) if position <= h.len() => h.position,
+            _ => return false,
+        };
+        if position < current {
+            (position..current).for_each(|_| {
+                self.history_back();
+            });
+        } else {
+            (current..position).for_each(|_| {
+                self.history_forward();
+            });
+        }
+        true
+    }
+
+    // Sets a state without recording it
+    fn apply(&mut self, path: &str, state: Option<Rc<dyn Any>>) {
+        match state {
+            Some(s) => self.state.insert(String::from(path), s),
+            // rendered again with its initial state
+            None => self.state.remove(path),
+        };
+        self.changed = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store(capacity: usize) -> StateStore {
+        let mut store = StateStore::new();
+        store.enable_history(capacity);
+        store
+    }
+
+    fn state(store: &StateStore, path: &str) -> Option<i32> {
+        store
+            .get(path)
+            .and_then(|s| s.downcast_ref::<i32>().copied())
+    }
+
+    fn values(store: &StateStore) -> Vec<i32> {
+        let history = store.history().unwrap();
+        history
+            .changes()
+            .map(|c| *c.new.downcast_ref::<i32>().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn the_oldest_changes_are_evicted() {
+        let mut store = store(3);
+        (1..=5).for_each(|n| store.set("/a", Rc::new(n)));
+        assert_eq!(values(&store), [3, 4, 5]);
+        assert_eq!(store.history().unwrap().position(), 3);
+    }
+
+    #[test]
+    fn changes_in_the_past_drop_the_undone_ones() {
+        let mut store = store(10);
+        (1..=3).for_each(|n| store.set("/a", Rc::new(n)));
+        assert!(store.history_back());
+        assert!(store.history_back());
+        assert_eq!(state(&store, "/a"), Some(1));
+
+        store.set("/a", Rc::new(7));
+        assert_eq!(values(&store), [1, 7]);
+        assert!(store.history().unwrap().is_at_present());
+        assert!(!store.history_forward());
+    }
+
+    #[test]
+    fn undoing_a_first_state_removes_it() {
+        let mut store = store(10);
+        store.set("/a", Rc::new(1));
+        assert!(store.history_back());
+        assert_eq!(state(&store, "/a"), None);
+        assert!(store.has_changed());
+        assert!(!store.history_back());
+
+        assert!(store.history_forward());
+        assert_eq!(state(&store, "/a"), Some(1));
+    }
+
+    #[test]
+    fn going_stays_within_the_history() {
+        let mut store = store(10);
+        (1..=3).for_each(|n| store.set("/a", Rc::new(n)));
+        store.set("/b", Rc::new(10));
+
+        assert!(store.history_go(1));
+        assert_eq!(state(&store, "/a"), Some(1));
+        assert_eq!(state(&store, "/b"), None);
+        assert!(store.history_go(4));
+        assert_eq!(state(&store, "/a"), Some(3));
+        assert_eq!(state(&store, "/b"), Some(10));
+
+        assert!(!store.history_go(5));
+        assert_eq!(store.history().unwrap().position(), 4);
+        assert!(store.history_go(0));
+        assert_eq!(state(&store, "/a"), None);
+    }
+}
@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::engine::*;
+
+// Renders of the components of a type during a render of the app
+#[derive(Clone, Debug)]
+pub struct ComponentMetrics {
+    pub type_name: &'static str,
+    pub count: usize,
+    // spent in Component::render, not in the rendering of the elements returned
+    pub duration: Duration,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct RenderMetrics {
+    // spent in render_app_to_graph
+    pub duration: Duration,
+    // slowest first
+    pub components: Vec<ComponentMetrics>,
+}
+
+impl RenderMetrics {
+    pub fn component_count(&self) -> usize {
+        self.components.iter().map(|c| c.count).sum()
+    }
+}
+
+// Measures of the render in progress, by component type
+#[derive(Debug, Default)]
+pub(crate) struct Profile {
+    start: Option<Instant>,
+    components: HashMap<&'static str, (usize, Duration)>,
+}
+
+impl Profile {
+    pub(crate) fn start(&mut self) {
+        self.start = Some(Instant::now());
+        self.components.clear();
+    }
+
+    pub(crate) fn add_component(&mut self, type_name: &'static str, duration: Duration) {
+        let entry = self.components.entry(type_name).or_default();
+        entry.0 += 1;
+        entry.1 += duration;
+    }
+
+    pub(crate) fn finish(&mut self) -> RenderMetrics {
+        let mut components: Vec<ComponentMetrics> = self
+            .components
+            .drain()
+            .map(|(type_name, (count, duration))| ComponentMetrics {
+                type_name,
+                count,
+                duration,
+            })
+            .collect();
+        components.sort_by_key(|c| std::cmp::Reverse(c.duration));
+        RenderMetrics {
+            duration: self.start.take().map_or(Duration::ZERO, |s| s.elapsed()),
+            components,
+        }
+    }
+}
+
+impl StateStore {
+    // Measures the next renders of the app
+    pub fn enable_metrics(&mut self) {
+        if self.profile.is_none() {
+            self.profile = Some(Profile::default());
+        }
+    }
+
+    pub fn disable_metrics(&mut self) {
+        self.profile = None;
+        self.render_metrics = None;
+    }
+
+    // Of the last render, once metrics are enabled
+    pub fn render_metrics(&self) -> Option<&RenderMetrics> {
+        self.render_metrics.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profiles_sum_by_component_type() {
+        let mut profile = Profile::default();
+        profile.start();
+        profile.add_component("app::Label", Duration::from_millis(1));
+        profile.add_component("app::Counter", Duration::from_millis(3));
+        profile.add_component("app::Label", Duration::from_millis(1));
+        let metrics = profile.finish();
+
+        let components: Vec<(&str, usize, Duration)> = metrics
+            .components
+            .iter()
+            .map(|c| (c.type_name, c.count, c.duration))
+            .collect();
+        // slowest first
+        assert_eq!(
+            components,
+            [
+                ("app::Counter", 1, Duration::from_millis(3)),
+                ("app::Label", 2, Duration::from_millis(2)),
+            ]
+        );
+        assert_eq!(metrics.component_count(), 3);
+    }
+
+    #[test]
+    fn profiles_start_over() {
+        let mut profile = Profile::default();
+        profile.start();
+        profile.add_component("app::Label", Duration::from_millis(1));
+        profile.finish();
+
+        // not started
+        let metrics = profile.finish();
+        assert_eq!(metrics.duration, Duration::ZERO);
+        assert!(metrics.components.is_empty());
+
+        profile.add_component("app::Label", Duration::from_millis(1));
+        profile.start();
+        assert!(profile.finish().components.is_empty());
+    }
+
+    struct Parent {}
+
+    struct Child {}
+
+    impl Component<()> for Parent {
+        fn render(&self, _: Rc<BoxedState>, _: Rc<SetState>) -> El<()> {
+            El::Container(vec![
+                El::Component(Box::new(Child {})),
+                El::Component(Box::new(Child {})),
+            ])
+        }
+    }
+
+    impl Component<()> for Child {
+        fn render(&self, _: Rc<BoxedState>, _: Rc<SetState>) -> El<()> {
+            El::None
+        }
+    }
+
+    #[test]
+    fn renders_are_measured_once_enabled() {
+        let store = new_state_store();
+        let app = El::Component(Box::new(Parent {}));
+        render_app_to_graph(&app, store.clone());
+        assert!(store.borrow().render_metrics().is_none());
+
+        store.borrow_mut().enable_metrics();
+        render_app_to_graph(&app, store.clone());
+        let counts: HashMap<&str, usize> = store
+            .borrow()
+            .render_metrics()
+            .unwrap()
+            .components
+            .iter()
+            .map(|c| (short_type_name(c.type_name), c.count))
+            .collect();
+        assert_eq!(
+            counts,
+            [("Parent", 1), ("Child", 2)].iter().copied().collect()
+        );
+
+        store.borrow_mut().disable_metrics();
+        assert!(store.borrow().render_metrics().is_none());
+    }
+}
@@ -1,13 +1,32 @@
-use std::any::Any;
+use std::any::{Any, TypeId};
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
+use std::time::Instant;
+
+pub mod boundary;
+pub mod dump;
+pub mod history;
+pub mod metrics;
+pub mod scheduler;
+pub mod snapshot;
+
+pub use boundary::*;
+pub use dump::*;
+pub use history::*;
+pub use metrics::*;
+pub use scheduler::*;
+pub use snapshot::*;
 
 pub enum El<TPayload> {
     None,
     Node(Node<TPayload, El<TPayload>>),
     Component(Box<dyn Component<TPayload>>),
     Container(Vec<El<TPayload>>),
+    // catches the errors of the render of its content
+    ErrorBoundary(Box<ErrorBoundary<TPayload>>),
+    // fails the render, up to the closest error boundary
+    Error(String),
 }
 
 pub enum RenderedEl<TPayload> {
@@ -51,15 +70,34 @@ impl<TPayload, TChild> Node<TPayload, TChild> {
 // Automatically implemented by macro for
 // all structs implementing trait Component
 pub trait KnowsType<TPayload> {
-    fn type_id(&self) -> std::any::TypeId;
+    // identifies the type of the component
+    fn type_id(&self) -> TypeId;
+    // readable: used in paths and messages. std does not guarantee it stays
+    // the same between compiler versions, so state snapshots and inspector
+    // paths saved before a toolchain upgrade may not match anymore.
+    fn type_name(&self) -> &'static str;
 }
 
 impl<T: 'static, U: 'static> KnowsType<U> for T
 where
     T: Component<U>,
 {
-    fn type_id(&self) -> std::any::TypeId {
-        std::any::TypeId::of::<T>()
+    fn type_id(&self) -> TypeId {
+        TypeId::of::<T>()
+    }
+
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+}
+
+// Type name without its module, e.g. Counter for app::Counter; the modules of
+// generic parameters are kept
+pub fn short_type_name(name: &str) -> &str {
+    let end = name.find('<').unwrap_or(name.len());
+    match name[..end].rfind("::") {
+        Some(i) => &name[i + 2..],
+        None => name,
     }
 }
 
@@ -72,11 +110,28 @@ pub trait Component<TPayload>: KnowsType<TPayload> {
     }
 
     fn render(&self, state: Rc<BoxedState>, set_state: Rc<SetState>) -> El<TPayload>;
+
+    // Called once the component is rendered for the first time; timers and
+    // background tasks scheduled with handle are cancelled when it is unmounted
+    fn did_mount(&self, _handle: &ComponentHandle) {}
 }
 
 #[derive(Debug)]
 pub struct StateStore {
     state: HashMap<String, Rc<dyn Any>>,
+    scheduler: Rc<RefCell<Scheduler>>,
+    // paths of the components rendered by the last render, and by the current one
+    mounted: HashSet<String>,
+    rendering: HashSet<String>,
+    // a state was set since the last render
+    changed: bool,
+    // None: changes are not recorded
+    history: Option<StateHistory>,
+    // type of the component a state belongs to, by path
+    component_types: HashMap<String, TypeId>,
+    // None: renders are not measured
+    profile: Option<Profile>,
+    render_metrics: Option<RenderMetrics>,
 }
 
 pub fn new_state_store() -> Rc<RefCell<StateStore>> {
@@ -87,13 +142,57 @@ impl StateStore {
     fn new() -> Self {
         Self {
             state: HashMap::new(),
+            scheduler: Rc::new(RefCell::new(Scheduler::new())),
+            mounted: HashSet::new(),
+            rendering: HashSet::new(),
+            changed: false,
+            history: None,
+            component_types: HashMap::new(),
+            profile: None,
+            render_metrics: None,
         }
     }
 
+    pub fn scheduler(&self) -> Rc<RefCell<Scheduler>> {
+        Rc::clone(&self.scheduler)
+    }
+
+    // Handle to update the state of the component at path from any thread
+    pub fn sync_set_state(&self, path: &str) -> SyncSetState {
+        self.scheduler.borrow().sync_set_state(path)
+    }
+
     pub fn set(&mut self, path: &str, state: Rc<dyn Any>) {
+        let old = self.state.insert(String::from(path), Rc::clone(&state));
+        self.record_change(StateChange {
+            path: String::from(path),
+            old,
+            new: state,
+            at: Instant::now(),
+        });
+        self.changed = true;
+    }
+
+    // Initial states are not changes: they are shown by the render storing them
+    fn set_initial(&mut self, path: &str, state: Rc<dyn Any>) {
         self.state.insert(String::from(path), state);
     }
 
+    // Whether the app needs to be rendered again to show the current states
+    pub fn has_changed(&self) -> bool {
+        self.changed
+    }
+
+    // For components showing more than their state, e.g. the store itself
+    pub fn request_render(&mut self) {
+        self.changed = true;
+    }
+
+    // Paths of the components rendered by the last render
+    pub fn mounted(&self) -> impl Iterator<Item = &str> {
+        self.mounted.iter().map(String::as_str)
+    }
+
     pub fn get(&self, path: &str) -> Option<Rc<dyn Any>> {
         match self.state.get(path) {
             None => None,
@@ -119,7 +218,11 @@ where
     fn must_receive_state_rc(&self, state: Rc<dyn Any>) -> Rc<U> {
         match self.receive_state_rc(state) {
             Ok(u) => u,
-            Err(_) => panic!("StateReceiver.must_receive_state_rc: could not cast state to expected type; this is a programming error"),
+            Err(_) => panic!(
+                "StateReceiver.must_receive_state_rc: could not cast the state of {} to {}; this is a programming error",
+                std::any::type_name::<T>(),
+                std::any::type_name::<U>()
+            ),
         }
     }
 
@@ -159,7 +262,12 @@ fn render<TPayload: 'static>(
         ),
         El::Component(c) => render_stateful_component(
             c,
-            format!("{}/{}~{:?}", path, sibling_num, c.type_id()),
+            format!(
+                "{}/{}~{}",
+                path,
+                sibling_num,
+                KnowsType::type_name(c.as_ref())
+            ),
             sibling_num,
             state_store,
         ),
@@ -169,6 +277,14 @@ fn render<TPayload: 'static>(
             sibling_num,
             state_store,
         ),
+        El::ErrorBoundary(b) => render_error_boundary(
+            b,
+            format!("{}/{}~ErrorBoundary", path, sibling_num),
+            state_store,
+        ),
+        El::Error(message) => {
+            raise_render_error(message, &format!("{}/{}~Error", path, sibling_num))
+        }
     }
 }
 
@@ -231,17 +347,130 @@ fn render_stateful_component<TPayload: 'static>(
             .set(path_clone.as_str(), Rc::clone(&s))
     });
 
-    let s = match state_store.borrow().get(path.as_str()) {
-        None => c.initial_state(),
+    let stored = {
+        let mut store = state_store.borrow_mut();
+        // a component of another type with the same name does not get its state
+        let type_id = KnowsType::type_id(c.as_ref());
+        if let Some(previous) = store.component_types.insert(path.clone(), type_id) {
+            if previous != type_id {
+                store.state.remove(&path);
+            }
+        }
+        store.get(path.as_str())
+    };
+    let s = match stored {
+        // stored so that handles read it before the first set_state
+        None => {
+            let s = c.initial_state();
+            state_store
+                .borrow_mut()
+                .set_initial(path.as_str(), Rc::clone(&s));
+            s
+        }
         Some(s) => s,
     };
 
-    render(&c.render(s, set_state), path, sibling_num, state_store)
+    enter_component(&path);
+    let start = Instant::now();
+    let el = c.render(s, set_state);
+    if let Some(profile) = &mut state_store.borrow_mut().profile {
+        profile.add_component(KnowsType::type_name(c.as_ref()), start.elapsed());
+    }
+
+    let rendered = render(&el, path.clone(), sibling_num, Rc::clone(&state_store));
+    leave_component();
+
+    let mounting = {
+        let mut store = state_store.borrow_mut();
+        store.rendering.insert(path.clone());
+        !store.mounted.contains(&path)
+    };
+    if mounting {
+        c.did_mount(&ComponentHandle::new(&path, &state_store));
+    }
+
+    rendered
 }
 
 pub fn render_app_to_graph<TPayload: 'static>(
     el: &El<TPayload>,
     state_store: Rc<RefCell<StateStore>>,
 ) -> RenderedEl<TPayload> {
-    render(el, "".to_string(), 0, state_store)
+    commit_posted_updates(&state_store);
+    {
+        let mut store = state_store.borrow_mut();
+        // the states changed so far are shown by this render; the ones set
+        // while rendering, e.g. by did_mount, need another one
+        store.changed = false;
+        if let Some(profile) = &mut store.profile {
+            profile.start();
+        }
+    }
+    let graph = render(el, "".to_string(), 0, Rc::clone(&state_store));
+
+    // components not rendered anymore are unmounted
+    let mut store = state_store.borrow_mut();
+    let rendered = std::mem::take(&mut store.rendering);
+    let scheduler = store.scheduler();
+    for path in store.mounted.difference(&rendered) {
+        scheduler.borrow_mut().cancel_path(path);
+    }
+    store.mounted = rendered;
+    if let Some(profile) = &mut store.profile {
+        store.render_metrics = Some(profile.finish());
+    }
+
+    graph
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Loads its data once mounted
+    struct Loader {}
+
+    impl Component<()> for Loader {
+        fn initial_state(&self) -> Rc<dyn Any> {
+            Rc::new("loading")
+        }
+
+        fn render(&self, _: Rc<BoxedState>, _: Rc<SetState>) -> El<()> {
+            El::None
+        }
+
+        fn did_mount(&self, handle: &ComponentHandle) {
+            handle.set_state(Rc::new("loaded"));
+        }
+    }
+
+    struct Static {}
+
+    impl Component<()> for Static {
+        fn render(&self, _: Rc<BoxedState>, _: Rc<SetState>) -> El<()> {
+            El::None
+        }
+    }
+
+    #[test]
+    fn initial_states_are_not_changes() {
+        let store = new_state_store();
+        render_app_to_graph(&El::Component(Box::new(Static {})), store.clone());
+        assert!(!store.borrow().has_changed());
+    }
+
+    #[test]
+    fn states_set_while_rendering_are_changes() {
+        let store = new_state_store();
+        let app = El::Component(Box::new(Loader {}));
+        render_app_to_graph(&app, store.clone());
+        assert!(store.borrow().has_changed());
+
+        // shown by the next render, which mounts nothing
+        render_app_to_graph(&app, store.clone());
+        assert!(!store.borrow().has_changed());
+        let path = store.borrow().mounted().next().unwrap().to_string();
+        let state = store.borrow().get(&path).unwrap();
+        assert_eq!(state.downcast_ref::<&str>(), Some(&"loaded"));
+    }
 }
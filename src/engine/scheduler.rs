@@ -0,0 +1,605 @@
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::rc::{Rc, Weak};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::engine::{BoxedState, StateStore};
+
+pub type TaskId = u64;
+
+// Shorter intervals would keep the UI thread busy running them
+pub const MIN_INTERVAL: Duration = Duration::from_millis(1);
+
+type TimerCallback = Rc<RefCell<dyn FnMut(&ComponentHandle)>>;
+type DoneCallback = Box<dyn FnOnce(&ComponentHandle, Box<dyn Any + Send>)>;
+// Computes the new state of a component from its current one, on the UI thread
+type PostedUpdate = Box<dyn FnOnce(Option<Rc<BoxedState>>) -> Option<Rc<BoxedState>> + Send>;
+
+// Sent to the UI thread by the other threads
+enum Posted {
+    // Err: the work panicked
+    TaskDone(TaskId, thread::Result<Box<dyn Any + Send>>),
+    StateUpdate(String, PostedUpdate),
+}
+
+type Waker = Box<dyn Fn() + Send>;
+
+// Sends to the UI thread, and wakes it up if it is waiting for something else
+#[derive(Clone)]
+struct Mailbox {
+    sender: Sender<Posted>,
+    waker: Arc<Mutex<Option<Waker>>>,
+}
+
+impl Mailbox {
+    // Returns false if the UI is gone
+    fn post(&self, posted: Posted) -> bool {
+        if self.sender.send(posted).is_err() {
+            return false;
+        }
+        if let Ok(waker) = self.waker.lock() {
+            if let Some(wake) = waker.as_ref() {
+                wake();
+            }
+        }
+        true
+    }
+}
+
+struct Timer {
+    id: TaskId,
+    // path of the component the timer belongs to
+    path: String,
+    due: Instant,
+    // None: runs once
+    every: Option<Duration>,
+    callback: TimerCallback,
+}
+
+struct Spawned {
+    path: String,
+    on_done: DoneCallback,
+}
+
+// Timers and background tasks of the components, run on the UI thread by
+// run_scheduled. Tasks of unmounted components are cancelled.
+pub struct Scheduler {
+    next_id: TaskId,
+    timers: Vec<Timer>,
+    spawned: HashMap<TaskId, Spawned>,
+    // other threads post their results and state updates to the UI thread
+    mailbox: Mailbox,
+    receiver: Receiver<Posted>,
+    // posted while waiting, not handled yet
+    received: Vec<Posted>,
+}
+
+impl fmt::Debug for Scheduler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Scheduler")
+            .field("timers", &self.timers.len())
+            .field("spawned", &self.spawned.len())
+            .finish()
+    }
+}
+
+impl Scheduler {
+    pub(crate) fn new() -> Self {
+        let (sender, receiver) = channel();
+        Scheduler {
+            next_id: 0,
+            timers: Vec::new(),
+            spawned: HashMap::new(),
+            mailbox: Mailbox {
+                sender,
+                waker: Arc::new(Mutex::new(None)),
+            },
+            receiver,
+            received: Vec::new(),
+        }
+    }
+
+    fn next_id(&mut self) -> TaskId {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    fn add_timer(
+        &mut self,
+        path: &str,
+        delay: Duration,
+        every: Option<Duration>,
+        callback: TimerCallback,
+    ) -> TaskId {
+        let id = self.next_id();
+        self.timers.push(Timer {
+            id,
+            path: path.to_string(),
+            due: Instant::now() + delay,
+            every,
+            callback,
+        });
+        id
+    }
+
+    fn spawn<T, W, D>(&mut self, path: &str, work: W, on_done: D) -> TaskId
+    where
+        T: Send + 'static,
+        W: FnOnce() -> T + Send + 'static,
+        D: FnOnce(&ComponentHandle, T) + 'static,
+    {
+        let id = self.next_id();
+        self.spawned.insert(
+            id,
+            Spawned {
+                path: path.to_string(),
+                on_done: Box::new(move |handle, result| {
+                    if let Ok(result) = result.downcast::<T>() {
+                        on_done(handle, *result);
+                    }
+                }),
+            },
+        );
+
+        let mailbox = self.mailbox.clone();
+        thread::spawn(move || {
+            // posted even if work panics, for the task to be known as done
+            let result = panic::catch_unwind(AssertUnwindSafe(work))
+                .map(|r| Box::new(r) as Box<dyn Any + Send>);
+            // the UI may be gone already
+            mailbox.post(Posted::TaskDone(id, result));
+        });
+        id
+    }
+
+    // A cancelled background task keeps running, but its result is dropped
+    pub fn cancel(&mut self, id: TaskId) {
+        self.timers.retain(|t| t.id != id);
+        self.spawned.remove(&id);
+    }
+
+    pub(crate) fn cancel_path(&mut self, path: &str) {
+        self.timers.retain(|t| t.path != path);
+        self.spawned.retain(|_, s| s.path != path);
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.timers.is_empty() && self.spawned.is_empty()
+    }
+
+    pub fn next_due(&self) -> Option<Instant> {
+        self.timers.iter().map(|t| t.due).min()
+    }
+
+    // Blocks until a timer is due, a background task is done, a state update is
+    // posted, or max elapsed
+    pub fn wait(&mut self, max: Duration) {
+        if !self.received.is_empty() {
+            return;
+        }
+        let now = Instant::now();
+        let deadline = match self.next_due() {
+            Some(due) if due < now + max => due,
+            _ => now + max,
+        };
+        match self
+            .receiver
+            .recv_timeout(deadline.saturating_duration_since(now))
+        {
+            Ok(result) => self.received.push(result),
+            Err(RecvTimeoutError::Timeout) | Err(RecvTimeoutError::Disconnected) => {}
+        }
+    }
+
+    // Callbacks to run now; the timers due again are rescheduled
+    fn take_ready(&mut self, now: Instant) -> Vec<(String, TimerCallback)> {
+        let mut ready = Vec::new();
+        for timer in self.timers.iter_mut().filter(|t| t.due <= now) {
+            ready.push((timer.path.clone(), timer.callback.clone()));
+            if let Some(every) = timer.every {
+                // late intervals skip the missed runs
+                timer.due = (timer.due + every).max(now);
+            }
+        }
+        self.timers.retain(|t| t.every.is_some() || t.due > now);
+        ready
+    }
+
+    fn receive(&mut self) {
+        let received: Vec<Posted> = self.receiver.try_iter().collect();
+        self.received.extend(received);
+    }
+
+    fn take_done(&mut self) -> Vec<(String, DoneCallback, Box<dyn Any + Send>)> {
+        self.receive();
+        let (done, others) = std::mem::take(&mut self.received)
+            .into_iter()
+            .partition(|p| matches!(p, Posted::TaskDone(_, _)));
+        self.received = others;
+
+        done.into_iter()
+            .filter_map(|p| match p {
+                // cancelled tasks are no longer known
+                Posted::TaskDone(id, result) => {
+                    let spawned = self.spawned.remove(&id)?;
+                    // the panic was reported on the thread of the task
+                    result.ok().map(|r| (spawned.path, spawned.on_done, r))
+                }
+                Posted::StateUpdate(_, _) => None,
+            })
+            .collect()
+    }
+
+    fn take_state_updates(&mut self) -> Vec<(String, PostedUpdate)> {
+        self.receive();
+        let (updates, others) = std::mem::take(&mut self.received)
+            .into_iter()
+            .partition(|p| matches!(p, Posted::StateUpdate(_, _)));
+        self.received = others;
+
+        updates
+            .into_iter()
+            .filter_map(|p| match p {
+                Posted::StateUpdate(path, update) => Some((path, update)),
+                Posted::TaskDone(_, _) => None,
+            })
+            .collect()
+    }
+
+    // Called from other threads after they post results or state updates, for
+    // a runtime blocked on something else than wait
+    pub fn set_waker(&self, waker: impl Fn() + Send + 'static) {
+        if let Ok(mut w) = self.mailbox.waker.lock() {
+            *w = Some(Box::new(waker));
+        }
+    }
+
+    pub fn sync_set_state(&self, path: &str) -> SyncSetState {
+        SyncSetState {
+            path: path.to_string(),
+            mailbox: self.mailbox.clone(),
+        }
+    }
+}
+
+// Applies the state updates posted by other threads, in the order they were posted.
+// Returns whether there were any.
+pub fn commit_posted_updates(state_store: &Rc<RefCell<StateStore>>) -> bool {
+    let scheduler = state_store.borrow().scheduler();
+    let updates = scheduler.borrow_mut().take_state_updates();
+    let committed = !updates.is_empty();
+
+    let mut store = state_store.borrow_mut();
+    for (path, update) in updates {
+        if let Some(state) = update(store.get(&path)) {
+            store.set(&path, state);
+        }
+    }
+    committed
+}
+
+// Runs the due timers and the callbacks of the finished background tasks.
+// Returns whether any ran, so the app needs to be rendered again.
+pub fn run_scheduled(state_store: &Rc<RefCell<StateStore>>) -> bool {
+    let committed = commit_posted_updates(state_store);
+    let scheduler = state_store.borrow().scheduler();
+    let ready = scheduler.borrow_mut().take_ready(Instant::now());
+    let done = scheduler.borrow_mut().take_done();
+    let ran = committed || !ready.is_empty() || !done.is_empty();
+
+    // callbacks may schedule other tasks: no borrow is held while they run
+    for (path, callback) in ready {
+        let handle = ComponentHandle::new(&path, state_store);
+        (callback.borrow_mut())(&handle);
+    }
+    for (path, on_done, result) in done {
+        on_done(&ComponentHandle::new(&path, state_store), result);
+    }
+    ran
+}
+
+// Blocks until scheduled work is ready to run, or max elapsed
+pub fn wait_scheduled(state_store: &Rc<RefCell<StateStore>>, max: Duration) {
+    let scheduler = state_store.borrow().scheduler();
+    scheduler.borrow_mut().wait(max);
+}
+
+// Access to the state and the scheduler of a mounted component, outside of render
+#[derive(Clone)]
+pub struct ComponentHandle {
+    path: String,
+    state_store: Weak<RefCell<StateStore>>,
+}
+
+impl ComponentHandle {
+    pub(crate) fn new(path: &str, state_store: &Rc<RefCell<StateStore>>) -> Self {
+        ComponentHandle {
+            path: path.to_string(),
+            state_store: Rc::downgrade(state_store),
+        }
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn state(&self) -> Option<Rc<BoxedState>> {
+        self.state_store.upgrade()?.borrow().get(&self.path)
+    }
+
+    pub fn set_state(&self, state: Rc<BoxedState>) {
+        if let Some(store) = self.state_store.upgrade() {
+            store.borrow_mut().set(&self.path, state);
+        }
+    }
+
+    // Sets the state to a modified copy of the current one, if it is a T
+    pub fn update_state<T: Clone + 'static>(&self, update: impl FnOnce(&mut T)) {
+        let current = self.state().and_then(|s| s.downcast_ref::<T>().cloned());
+        if let Some(mut state) = current {
+            update(&mut state);
+            self.set_state(Rc::new(state));
+        }
+    }
+
+    fn with_scheduler(&self, f: impl FnOnce(&mut Scheduler) -> TaskId) -> TaskId {
+        match self.state_store.upgrade() {
+            Some(store) => {
+                let scheduler = store.borrow().scheduler();
+                let id = f(&mut scheduler.borrow_mut());
+                id
+            }
+            None => 0,
+        }
+    }
+
+    // Calls callback once, after delay
+    pub fn set_timeout(
+        &self,
+        delay: Duration,
+        callback: impl FnMut(&ComponentHandle) + 'static,
+    ) -> TaskId {
+        self.with_scheduler(|s| {
+            s.add_timer(&self.path, delay, None, Rc::new(RefCell::new(callback)))
+        })
+    }
+
+    // Calls callback every period, until cancelled. Periods shorter than
+    // MIN_INTERVAL are MIN_INTERVAL.
+    pub fn set_interval(
+        &self,
+        every: Duration,
+        callback: impl FnMut(&ComponentHandle) + 'static,
+    ) -> TaskId {
+        let every = every.max(MIN_INTERVAL);
+        self.with_scheduler(|s| {
+            s.add_timer(
+                &self.path,
+                every,
+                Some(every),
+                Rc::new(RefCell::new(callback)),
+            )
+        })
+    }
+
+    // Runs work on a new thread, then on_done with its result on the UI thread.
+    // on_done is not called if work panics.
+    pub fn spawn<T, W, D>(&self, work: W, on_done: D) -> TaskId
+    where
+        T: Send + 'static,
+        W: FnOnce() -> T + Send + 'static,
+        D: FnOnce(&ComponentHandle, T) + 'static,
+    {
+        self.with_scheduler(|s| s.spawn(&self.path, work, on_done))
+    }
+
+    pub fn cancel(&self, id: TaskId) {
+        if let Some(store) = self.state_store.upgrade() {
+            let scheduler = store.borrow().scheduler();
+            scheduler.borrow_mut().cancel(id);
+        }
+    }
+
+    // Handle to update the state of the component from any thread
+    pub fn sync_set_state(&self) -> Option<SyncSetState> {
+        let store = self.state_store.upgrade()?;
+        let scheduler = store.borrow().scheduler();
+        let handle = scheduler.borrow().sync_set_state(&self.path);
+        Some(handle)
+    }
+}
+
+// Send + Sync counterpart of SetState: updates are posted to the UI thread, and
+// committed there before the next render
+#[derive(Clone)]
+pub struct SyncSetState {
+    path: String,
+    mailbox: Mailbox,
+}
+
+impl SyncSetState {
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    // Returns false if the UI is gone
+    pub fn set<T: Any + Send>(&self, state: T) -> bool {
+        self.post(Box::new(move |_| Some(Rc::new(state))))
+    }
+
+    // Sets the state to a modified copy of the current one, if it is a T
+    pub fn update<T: Clone + 'static>(&self, update: impl FnOnce(&mut T) + Send + 'static) -> bool {
+        self.post(Box::new(move |current| {
+            let mut state = current?.downcast_ref::<T>()?.clone();
+            update(&mut state);
+            Some(Rc::new(state))
+        }))
+    }
+
+    fn post(&self, update: PostedUpdate) -> bool {
+        self.mailbox
+            .post(Posted::StateUpdate(self.path.clone(), update))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{new_state_store, render_app_to_graph, Component, El, SetState};
+
+    // Runs the scheduled work until the scheduler is idle
+    fn run_until_idle(state_store: &Rc<RefCell<StateStore>>) {
+        let scheduler = state_store.borrow().scheduler();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !scheduler.borrow().is_idle() {
+            assert!(Instant::now() < deadline, "the scheduler never became idle");
+            wait_scheduled(state_store, Duration::from_millis(50));
+            run_scheduled(state_store);
+        }
+    }
+
+    #[test]
+    fn spawned_results_reach_the_ui_thread() {
+        let store = new_state_store();
+        let handle = ComponentHandle::new("/0~Task", &store);
+        let result = Rc::new(RefCell::new(None));
+        let received = Rc::clone(&result);
+        handle.spawn(|| 6 * 7, move |_, r| *received.borrow_mut() = Some(r));
+
+        run_until_idle(&store);
+        assert_eq!(*result.borrow(), Some(42));
+    }
+
+    #[test]
+    fn panicking_tasks_are_done() {
+        let store = new_state_store();
+        let handle = ComponentHandle::new("/0~Task", &store);
+        let called = Rc::new(RefCell::new(false));
+        let on_done = Rc::clone(&called);
+        // a panic payload of the type of the result is not taken for a result
+        handle.spawn(
+            || -> String { panic!("work failed") },
+            move |_, _| *on_done.borrow_mut() = true,
+        );
+
+        run_until_idle(&store);
+        assert!(!*called.borrow());
+    }
+
+    fn counter() -> (Rc<RefCell<u32>>, impl FnMut(&ComponentHandle)) {
+        let count = Rc::new(RefCell::new(0));
+        let counted = Rc::clone(&count);
+        (count, move |_: &ComponentHandle| *counted.borrow_mut() += 1)
+    }
+
+    #[test]
+    fn timeouts_run_once() {
+        let store = new_state_store();
+        let handle = ComponentHandle::new("/0~Timer", &store);
+        let (count, callback) = counter();
+        handle.set_timeout(Duration::from_millis(5), callback);
+
+        assert!(!run_scheduled(&store));
+        run_until_idle(&store);
+        assert_eq!(*count.borrow(), 1);
+    }
+
+    #[test]
+    fn intervals_run_until_cancelled() {
+        let store = new_state_store();
+        let handle = ComponentHandle::new("/0~Timer", &store);
+        let count = Rc::new(RefCell::new(0));
+        let counted = Rc::clone(&count);
+        let id = Rc::new(RefCell::new(0));
+        let interval = Rc::clone(&id);
+        *id.borrow_mut() = handle.set_interval(Duration::from_millis(1), move |handle| {
+            *counted.borrow_mut() += 1;
+            if *counted.borrow() == 3 {
+                handle.cancel(*interval.borrow());
+            }
+        });
+
+        run_until_idle(&store);
+        assert_eq!(*count.borrow(), 3);
+    }
+
+    #[test]
+    fn cancelled_timers_do_not_run() {
+        let store = new_state_store();
+        let handle = ComponentHandle::new("/0~Timer", &store);
+        let (count, callback) = counter();
+        let id = handle.set_timeout(Duration::ZERO, callback);
+        handle.cancel(id);
+
+        assert!(store.borrow().scheduler().borrow().is_idle());
+        assert!(!run_scheduled(&store));
+        assert_eq!(*count.borrow(), 0);
+    }
+
+    #[test]
+    fn zero_intervals_are_the_shortest_ones() {
+        let store = new_state_store();
+        let handle = ComponentHandle::new("/0~Timer", &store);
+        let (count, callback) = counter();
+        handle.set_interval(Duration::ZERO, callback);
+
+        let scheduler = store.borrow().scheduler();
+        let now = Instant::now() + MIN_INTERVAL;
+        assert_eq!(scheduler.borrow_mut().take_ready(now).len(), 1);
+        // not due again right away
+        assert!(scheduler.borrow().next_due().unwrap() > now);
+        assert_eq!(*count.borrow(), 0);
+    }
+
+    // Ticks while mounted
+    struct Ticker {}
+
+    impl Component<()> for Ticker {
+        fn render(&self, _: Rc<BoxedState>, _: Rc<SetState>) -> El<()> {
+            El::None
+        }
+
+        fn did_mount(&self, handle: &ComponentHandle) {
+            handle.set_interval(Duration::from_secs(1), |_| {});
+            handle.spawn(|| (), |_, _| {});
+        }
+    }
+
+    #[test]
+    fn unmounting_cancels_the_tasks() {
+        let store = new_state_store();
+        let scheduler = store.borrow().scheduler();
+        render_app_to_graph(&El::Component(Box::new(Ticker {})), store.clone());
+        assert_eq!(scheduler.borrow().timers.len(), 1);
+        assert_eq!(scheduler.borrow().spawned.len(), 1);
+
+        render_app_to_graph(&El::<()>::None, store.clone());
+        assert!(scheduler.borrow().is_idle());
+    }
+
+    #[test]
+    fn states_set_from_other_threads_are_committed_in_order() {
+        let store = new_state_store();
+        let handle = ComponentHandle::new("/0~Worker", &store);
+        let set_state = handle.sync_set_state().unwrap();
+        thread::spawn(move || {
+            set_state.set(1);
+            set_state.update(|n: &mut i32| *n += 1);
+            // not an i32: left as it is
+            set_state.update(|s: &mut String| s.push('!'));
+        })
+        .join()
+        .unwrap();
+
+        assert!(commit_posted_updates(&store));
+        let state = handle.state().unwrap();
+        assert_eq!(state.downcast_ref::<i32>(), Some(&2));
+        assert!(!commit_posted_updates(&store));
+    }
+}
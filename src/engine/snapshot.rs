@@ -0,0 +1,342 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::rc::Rc;
+
+use crate::engine::*;
+
+type SerializeFn = Box<dyn Fn(&dyn Any) -> Option<String>>;
+type DeserializeFn = Box<dyn Fn(&str) -> Option<Rc<dyn Any>>>;
+
+struct Registered {
+    serialize: SerializeFn,
+    deserialize: DeserializeFn,
+}
+
+// Component types whose states can be saved in a snapshot, each with the
+// functions writing its state to and reading it from a string. States of other
+// components are left out.
+#[derive(Default)]
+pub struct StateRegistry {
+    // by type name, as in the paths: TypeIds change between builds. Type names
+    // may change between compiler versions, leaving the snapshots saved before
+    // a toolchain upgrade unloaded.
+    by_component: HashMap<&'static str, Registered>,
+}
+
+impl StateRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // C is the type of the component, S the type of its state, as returned by
+    // initial_state
+    pub fn register<C: 'static, S: Any>(
+        mut self,
+        serialize: impl Fn(&S) -> String + 'static,
+        deserialize: impl Fn(&str) -> Option<S> + 'static,
+    ) -> Self {
+        self.by_component.insert(
+            std::any::type_name::<C>(),
+            Registered {
+                serialize: Box::new(move |state| state.downcast_ref::<S>().map(&serialize)),
+                deserialize: Box::new(move |data| {
+                    deserialize(data).map(|s| Rc::new(s) as Rc<dyn Any>)
+                }),
+            },
+        );
+        self
+    }
+
+    // Component type and data of the state of the component at path, on one line
+    pub fn describe(&self, path: &str, state: &dyn Any) -> Option<String> {
+        self.serialize(path, state)
+            .map(|(name, data)| format!("{} {}", short_type_name(name), data.replace('\n', " ")))
+    }
+
+    fn serialize<'a>(&self, path: &'a str, state: &dyn Any) -> Option<(&'a str, String)> {
+        let name = component_type(path)?;
+        let data = (self.by_component.get(name)?.serialize)(state)?;
+        Some((name, data))
+    }
+
+    fn deserialize(&self, name: &str, data: &str) -> Option<Rc<dyn Any>> {
+        (self.by_component.get(name)?.deserialize)(data)
+    }
+}
+
+// Type name of the component a state path belongs to: its last segment, e.g.
+// app::Counter for /0~app::App/1~app::Counter
+fn component_type(path: &str) -> Option<&str> {
+    path.rsplit('/').next()?.split_once('~').map(|(_, t)| t)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SnapshotEntry {
+    pub path: String,
+    // type name of the component, last in the path
+    pub name: String,
+    pub data: String,
+}
+
+// States of the components, by path, written by the registered serializers
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StateSnapshot {
+    pub entries: Vec<SnapshotEntry>,
+}
+
+const SNAPSHOT_HEADER: &str = "reust-state-snapshot 1";
+
+impl StateSnapshot {
+    // One line per entry: path, name and data separated by tabs, with tabs,
+    // newlines and backslashes escaped
+    pub fn to_text(&self) -> String {
+        let mut text = format!("{}\n", SNAPSHOT_HEADER);
+        for e in &self.entries {
+            text.push_str(&format!(
+                "{}\t{}\t{}\n",
+                escape(&e.path),
+                escape(&e.name),
+                escape(&e.data)
+            ));
+        }
+        text
+    }
+
+    pub fn from_text(text: &str) -> io::Result<Self> {
+        let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+
+        let mut lines = text.lines();
+        if lines.next() != Some(SNAPSHOT_HEADER) {
+            return Err(invalid("not a state snapshot"));
+        }
+        let entries = lines
+            .filter(|l| !l.is_empty())
+            .map(|l| {
+                let fields: Vec<&str> = l.split('\t').collect();
+                match fields.as_slice() {
+                    [path, name, data] => Ok(SnapshotEntry {
+                        path: unescape(path),
+                        name: unescape(name),
+                        data: unescape(data),
+                    }),
+                    _ => Err(invalid("malformed state snapshot entry")),
+                }
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+        Ok(StateSnapshot { entries })
+    }
+
+    pub fn save(&self, file: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(file, self.to_text())
+    }
+
+    pub fn load(file: impl AsRef<Path>) -> io::Result<Self> {
+        StateSnapshot::from_text(&fs::read_to_string(file)?)
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+}
+
+fn unescape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => out.push('\t'),
+            Some('n') => out.push('\n'),
+            Some(c) => out.push(c),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+impl StateStore {
+    // States of registered components, sorted by path
+    pub fn snapshot(&self, registry: &StateRegistry) -> StateSnapshot {
+        let mut entries: Vec<SnapshotEntry> = self
+            .state
+            .iter()
+            .filter_map(|(path, state)| {
+                let (name, data) = registry.serialize(path, state.as_ref())?;
+                Some(SnapshotEntry {
+                    path: path.clone(),
+                    name: String::from(name),
+                    data,
+                })
+            })
+            .collect();
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+        StateSnapshot { entries }
+    }
+
+    // Sets the states of the snapshot: restored before the first render, they
+    // replace the initial states. Entries of components not registered or not
+    // last in their path, that do not deserialize, or whose path holds a state
+    // of another type are skipped. Restored states are not recorded in the
+    // history. Returns the number of states restored.
+    pub fn restore(&mut self, registry: &StateRegistry, snapshot: &StateSnapshot) -> usize {
+        let mut restored = 0;
+        for e in &snapshot.entries {
+            if component_type(&e.path) != Some(e.name.as_str()) {
+                continue;
+            }
+            // the data of a state type since changed does not deserialize
+            let state = match registry.deserialize(&e.name, &e.data) {
+                Some(s) => s,
+                None => continue,
+            };
+            let current = self.state.get(&e.path).map(|s| s.as_ref().type_id());
+            if current.is_some() && current != Some(state.as_ref().type_id()) {
+                continue;
+            }
+            self.state.insert(e.path.clone(), state);
+            self.changed = true;
+            restored += 1;
+        }
+        restored
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Counter {}
+
+    struct Other {}
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct CounterState {
+        count: i32,
+    }
+
+    impl StateReceiver<CounterState> for Counter {}
+    impl Component<()> for Counter {
+        fn initial_state(&self) -> Rc<dyn Any> {
+            Rc::new(CounterState { count: 0 })
+        }
+
+        fn render(&self, _: Rc<BoxedState>, _: Rc<SetState>) -> El<()> {
+            El::None
+        }
+    }
+
+    fn registry() -> StateRegistry {
+        StateRegistry::new().register::<Counter, CounterState>(
+            |s| s.count.to_string(),
+            |data| {
+                Some(CounterState {
+                    count: data.parse().ok()?,
+                })
+            },
+        )
+    }
+
+    fn counter_path() -> String {
+        format!("/0~{}", std::any::type_name::<Counter>())
+    }
+
+    fn entry(path: &str, name: &str, data: &str) -> SnapshotEntry {
+        SnapshotEntry {
+            path: String::from(path),
+            name: String::from(name),
+            data: String::from(data),
+        }
+    }
+
+    #[test]
+    fn text_round_trip() {
+        let snapshot = StateSnapshot {
+            entries: vec![
+                entry("/0~a::B", "a::B", "plain"),
+                entry("/0~a::B/1~c", "c", "tab\there\nnewline"),
+                entry("/0~d", "d", "trailing backslash\\"),
+                entry("/0~e", "e", "\\t is not a tab, \\\\n not a newline"),
+                entry("/0~f", "f", ""),
+            ],
+        };
+        let text = snapshot.to_text();
+        assert_eq!(text.lines().count(), 6);
+        assert_eq!(StateSnapshot::from_text(&text).unwrap(), snapshot);
+    }
+
+    #[test]
+    fn unescape_reverses_escape() {
+        for s in ["", "\\", "a\\", "\\\\t", "\t\n\\", "x\\ny"] {
+            assert_eq!(unescape(&escape(s)), s);
+        }
+        // lenient with an unescaped trailing backslash
+        assert_eq!(unescape("a\\"), "a\\");
+    }
+
+    #[test]
+    fn from_text_rejects_other_files() {
+        assert!(StateSnapshot::from_text("not a snapshot\n").is_err());
+        assert!(StateSnapshot::from_text(&format!("{}\nno tabs\n", SNAPSHOT_HEADER)).is_err());
+    }
+
+    #[test]
+    fn snapshot_and_restore() {
+        let registry = registry();
+        let store = new_state_store();
+        render_app_to_graph(&El::Component(Box::new(Counter {})), store.clone());
+        store
+            .borrow_mut()
+            .set(&counter_path(), Rc::new(CounterState { count: 3 }));
+
+        let snapshot = store.borrow().snapshot(&registry);
+        assert_eq!(
+            snapshot.entries,
+            vec![entry(
+                &counter_path(),
+                std::any::type_name::<Counter>(),
+                "3"
+            )]
+        );
+
+        let restored = new_state_store();
+        restored.borrow_mut().enable_history(10);
+        assert_eq!(restored.borrow_mut().restore(&registry, &snapshot), 1);
+        let state = restored.borrow().get(&counter_path()).unwrap();
+        assert_eq!(state.downcast_ref(), Some(&CounterState { count: 3 }));
+        assert!(restored.borrow().history().unwrap().is_empty());
+    }
+
+    #[test]
+    fn restore_skips_mismatching_entries() {
+        let registry = registry();
+        let counter = std::any::type_name::<Counter>();
+        let other = format!("/0~{}", std::any::type_name::<Other>());
+        let snapshot = StateSnapshot {
+            entries: vec![
+                // another component at the path
+                entry(&other, counter, "1"),
+                // data of an older state type
+                entry(&counter_path(), counter, "count=1"),
+                // not registered
+                entry(&other, std::any::type_name::<Other>(), "1"),
+            ],
+        };
+        let store = new_state_store();
+        assert_eq!(store.borrow_mut().restore(&registry, &snapshot), 0);
+
+        // a state of another type at the path
+        store.borrow_mut().set(&counter_path(), Rc::new(1u8));
+        let snapshot = StateSnapshot {
+            entries: vec![entry(&counter_path(), counter, "1")],
+        };
+        assert_eq!(store.borrow_mut().restore(&registry, &snapshot), 0);
+    }
+}
@@ -0,0 +1,223 @@
+use std::fmt::Write;
+
+use crate::prelude::*;
+
+// Elements with no content nor closing tag
+const VOID_TAGS: [&str; 14] = [
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+// An element, or a text node when tag is empty
+#[derive(Clone, Default)]
+pub struct HtmlNode {
+    pub tag: String,
+    // written in order
+    pub attributes: Vec<(String, String)>,
+    // escaped, and written before the children
+    pub text: Option<String>,
+}
+
+impl HtmlNode {
+    pub fn new(tag: &str) -> Self {
+        Self {
+            tag: String::from(tag),
+            ..Default::default()
+        }
+    }
+
+    pub fn new_text(text: &str) -> Self {
+        Self {
+            text: Some(String::from(text)),
+            ..Default::default()
+        }
+    }
+
+    // Replaces the value of an attribute already set
+    pub fn set_attribute(mut self, name: &str, value: &str) -> Self {
+        match self.attributes.iter_mut().find(|(n, _)| n == name) {
+            Some(attr) => attr.1 = String::from(value),
+            None => self
+                .attributes
+                .push((String::from(name), String::from(value))),
+        }
+        self
+    }
+
+    pub fn set_text(mut self, text: &str) -> Self {
+        self.text = Some(String::from(text));
+        self
+    }
+
+    pub fn is_text(&self) -> bool {
+        self.tag.is_empty()
+    }
+}
+
+#[allow(dead_code)]
+pub fn element(tag: &str) -> Node<HtmlNode, El<HtmlNode>> {
+    Node::new(HtmlNode::new(tag))
+}
+
+#[allow(dead_code)]
+pub fn text(text: &str) -> El<HtmlNode> {
+    El::Node(Node::new(HtmlNode::new_text(text)))
+}
+
+// Serializer for dump_value, to_json and debug_dump
+pub fn html_payload(node: &HtmlNode) -> DumpValue {
+    DumpValue::object(vec![
+        ("tag", node.tag.as_str().into()),
+        (
+            "attributes",
+            DumpValue::Object(
+                node.attributes
+                    .iter()
+                    .map(|(n, v)| (n.clone(), v.as_str().into()))
+                    .collect(),
+            ),
+        ),
+        ("text", node.text.clone().into()),
+    ])
+}
+
+// The graph as an HTML fragment, without whitespace between the elements
+pub fn render_to_html(e: &RenderedEl<HtmlNode>) -> String {
+    let mut out = String::new();
+    write_element(&mut out, e);
+    out
+}
+
+// A whole page, with the graph as its body
+pub fn render_document(title: &str, e: &RenderedEl<HtmlNode>) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n</head>\n<body>\n{}\n</body>\n</html>\n",
+        escape(title),
+        render_to_html(e)
+    )
+}
+
+fn write_element(out: &mut String, e: &RenderedEl<HtmlNode>) {
+    match e {
+        RenderedEl::None => {}
+        RenderedEl::Node(rel) => {
+            let node = &rel.payload;
+            // elements with an invalid tag are left out, not their content
+            if node.is_text() || !is_valid_name(&node.tag) {
+                out.push_str(&escape(node.text.as_deref().unwrap_or("")));
+                for ch in &rel.children {
+                    write_element(out, ch);
+                }
+                return;
+            }
+
+            let _ = write!(out, "<{}", node.tag);
+            // attributes with an invalid name are skipped
+            for (name, value) in node.attributes.iter().filter(|(n, _)| is_valid_name(n)) {
+                let _ = write!(out, " {}=\"{}\"", name, escape(value));
+            }
+            out.push('>');
+            if VOID_TAGS.contains(&node.tag.as_str()) {
+                return;
+            }
+
+            if let Some(text) = &node.text {
+                out.push_str(&escape(text));
+            }
+            for ch in &rel.children {
+                write_element(out, ch);
+            }
+            let _ = write!(out, "</{}>", node.tag);
+        }
+        RenderedEl::Container(cont) => {
+            for ch in cont {
+                write_element(out, ch);
+            }
+        }
+    }
+}
+
+// Tag and attribute names are written as is: an ASCII letter, then ASCII
+// letters, digits, '-', '_' or ':'
+pub fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ':'))
+}
+
+// Safe in text and in double quoted attribute values
+pub fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn html(el: El<HtmlNode>) -> String {
+        render_to_html(&render_app_to_graph(&el, new_state_store()))
+    }
+
+    #[test]
+    fn escapes_text_and_attribute_values() {
+        let el = El::Node(
+            Node::new(HtmlNode::new("a").set_attribute("title", "\"x\" & 'y' <z>"))
+                .add_child(text("<b>&</b>")),
+        );
+        assert_eq!(
+            html(el),
+            "<a title=\"&quot;x&quot; &amp; &#39;y&#39; &lt;z&gt;\">&lt;b&gt;&amp;&lt;/b&gt;</a>"
+        );
+    }
+
+    #[test]
+    fn validates_names() {
+        assert!(is_valid_name("div"));
+        assert!(is_valid_name("data-x_1:y"));
+        assert!(!is_valid_name(""));
+        assert!(!is_valid_name("1a"));
+        assert!(!is_valid_name("-a"));
+        assert!(!is_valid_name("div onmouseover=alert(1)"));
+        assert!(!is_valid_name("x\"><script>"));
+    }
+
+    #[test]
+    fn skips_invalid_tags_and_attributes() {
+        let el = El::Node(
+            element("div onmouseover=alert(1)").add_child(El::Node(
+                Node::new(
+                    HtmlNode::new("a")
+                        .set_attribute("x\"><script>alert(2)</script><i y", "v")
+                        .set_attribute("href", "#"),
+                )
+                .add_child(text("link")),
+            )),
+        );
+        assert_eq!(html(el), "<a href=\"#\">link</a>");
+    }
+
+    #[test]
+    fn writes_the_children_of_text_nodes() {
+        let el = El::Node(Node::new(HtmlNode::new_text("a ")).add_child(text("& b")));
+        assert_eq!(html(el), "a &amp; b");
+    }
+
+    #[test]
+    fn void_elements_have_no_closing_tag() {
+        let el = El::Node(
+            element("p").add_children(vec![El::Node(Node::new(HtmlNode::new("br"))), text("x")]),
+        );
+        assert_eq!(html(el), "<p><br>x</p>");
+    }
+}
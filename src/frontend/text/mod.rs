@@ -1,8 +1,21 @@
+use std::cell::RefCell;
+use std::io::{self, BufRead, Write};
+use std::rc::Rc;
+use std::sync::mpsc::{channel, RecvTimeoutError};
+use std::thread;
+use std::time::Instant;
+
 use crate::prelude::*;
 
-#[derive(Clone)]
+pub type ClickHandler = Rc<dyn Fn()>;
+// Called with the text typed after the command
+pub type InputHandler = Rc<dyn Fn(String)>;
+
+#[derive(Clone, Default)]
 pub struct TextNode {
     pub text: String,
+    pub on_click: Option<ClickHandler>,
+    pub on_input: Option<InputHandler>,
 }
 
 impl TextNode {
@@ -10,8 +23,33 @@ impl TextNode {
     pub fn new(text: &str) -> Self {
         Self {
             text: String::from(text),
+            ..Default::default()
         }
     }
+
+    pub fn set_on_click(mut self, handler: Option<ClickHandler>) -> Self {
+        self.on_click = handler;
+        self
+    }
+
+    pub fn set_on_input(mut self, handler: Option<InputHandler>) -> Self {
+        self.on_input = handler;
+        self
+    }
+
+    // Nodes with handlers are numbered, for the commands to refer to them
+    pub fn is_actionable(&self) -> bool {
+        self.on_click.is_some() || self.on_input.is_some()
+    }
+}
+
+// Serializer for dump_value, to_json and debug_dump
+pub fn text_payload(node: &TextNode) -> DumpValue {
+    DumpValue::object(vec![
+        ("text", node.text.as_str().into()),
+        ("on_click", node.on_click.is_some().into()),
+        ("on_input", node.on_input.is_some().into()),
+    ])
 }
 
 #[allow(dead_code)]
@@ -19,29 +57,515 @@ pub fn node(text: &str) -> Node<TextNode, El<TextNode>> {
     Node::new(TextNode::new(text))
 }
 
+// How a TextWriter writes successive renders of the graph
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OutputMode {
+    // clears the terminal, then writes the graph: for terminals
+    Clear,
+    // writes the whole graph after a marker line
+    Full,
+    // writes only the lines changed since the previous render, after a marker
+    // line: for logs and pipes
+    Changes,
+}
+
+pub struct TextWriter<W: Write> {
+    out: W,
+    mode: OutputMode,
+    previous: Option<Vec<String>>,
+    frame: usize,
+}
+
+impl<W: Write> TextWriter<W> {
+    pub fn new(out: W, mode: OutputMode) -> Self {
+        TextWriter {
+            out,
+            mode,
+            previous: None,
+            frame: 0,
+        }
+    }
+
+    pub fn mode(&self) -> OutputMode {
+        self.mode
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+
+    pub fn draw(&mut self, e: &RenderedEl<TextNode>) -> io::Result<()> {
+        let lines = graph_lines(e);
+        if self.mode != OutputMode::Clear && self.previous.as_ref() == Some(&lines) {
+            return Ok(());
+        }
+        self.frame += 1;
+
+        match (self.mode, &self.previous) {
+            (OutputMode::Clear, _) => {
+                write!(self.out, "\x1b[0;0H\x1b[2J")?;
+                write_lines(&mut self.out, &lines)?;
+            }
+            (OutputMode::Full, _) | (OutputMode::Changes, None) => {
+                writeln!(self.out, "--- frame {} ---", self.frame)?;
+                write_lines(&mut self.out, &lines)?;
+            }
+            (OutputMode::Changes, Some(previous)) => {
+                writeln!(self.out, "--- frame {} (changes) ---", self.frame)?;
+                // changed lines, with their line number
+                for (i, line) in lines.iter().enumerate() {
+                    if previous.get(i) != Some(line) {
+                        writeln!(self.out, "{}: {}", i + 1, line)?;
+                    }
+                }
+                if previous.len() > lines.len() {
+                    writeln!(self.out, "{}-{}: removed", lines.len() + 1, previous.len())?;
+                }
+            }
+        }
+
+        self.previous = Some(lines);
+        self.out.flush()
+    }
+}
+
+fn write_lines(out: &mut impl Write, lines: &[String]) -> io::Result<()> {
+    for line in lines {
+        writeln!(out, "{}", line)?;
+    }
+    Ok(())
+}
+
+pub fn stdout_is_tty() -> bool {
+    unsafe { libc::isatty(libc::STDOUT_FILENO) == 1 }
+}
+
+thread_local! {
+    // Clear when stdout is a terminal, Changes when redirected
+    static STDOUT_WRITER: RefCell<TextWriter<io::Stdout>> = RefCell::new(TextWriter::new(
+        io::stdout(),
+        if stdout_is_tty() {
+            OutputMode::Clear
+        } else {
+            OutputMode::Changes
+        },
+    ));
+}
+
 #[allow(dead_code)]
 pub fn draw_graph(e: RenderedEl<TextNode>) {
-    clear_screen();
-    draw_element(e, 0);
+    STDOUT_WRITER.with(|writer| {
+        let _ = writer.borrow_mut().draw(&e);
+    });
 }
 
-fn clear_screen() {
-    println!("\x1b[0;0H\x1b[2J");
+// Lines of the graph, children indented under their parent
+pub fn graph_lines(e: &RenderedEl<TextNode>) -> Vec<String> {
+    fn walk(e: &RenderedEl<TextNode>, level: usize, lines: &mut Vec<String>) {
+        match e {
+            RenderedEl::None => {}
+            RenderedEl::Node(rel) => {
+                lines.push(format!("{}{}", "    ".repeat(level), rel.payload.text));
+                for ch in &rel.children {
+                    walk(ch, level + 1, lines);
+                }
+            }
+            RenderedEl::Container(cont) => {
+                for ch in cont {
+                    walk(ch, level, lines);
+                }
+            }
+        }
+    }
+
+    let mut lines = Vec::new();
+    walk(e, 0, &mut lines);
+    lines
 }
 
-fn draw_element(e: RenderedEl<TextNode>, level: usize) {
-    match e {
-        RenderedEl::None => {}
-        RenderedEl::Node(rel) => {
-            println!("{}{}", "    ".repeat(level), rel.payload.text);
-            for ch in rel.children {
-                draw_element(ch, level + 1);
+// The graph as draw_graph writes it, without escapes: for tests
+pub fn render_to_string(e: &RenderedEl<TextNode>) -> String {
+    graph_lines(e).iter().map(|l| format!("{}\n", l)).collect()
+}
+
+// Lines of the graph, actionable nodes prefixed with their number
+pub fn numbered_lines(e: &RenderedEl<TextNode>) -> Vec<String> {
+    fn walk(e: &RenderedEl<TextNode>, level: usize, count: &mut usize, lines: &mut Vec<String>) {
+        match e {
+            RenderedEl::None => {}
+            RenderedEl::Node(rel) => {
+                let node = &rel.payload;
+                let prefix = if node.is_actionable() {
+                    *count += 1;
+                    format!("[{}] ", count)
+                } else {
+                    "".to_string()
+                };
+                let hint = match (&node.on_click, &node.on_input) {
+                    (_, Some(_)) => format!(" (input {} <text>)", count),
+                    _ => "".to_string(),
+                };
+                lines.push(format!(
+                    "{}{}{}{}",
+                    "    ".repeat(level),
+                    prefix,
+                    node.text,
+                    hint
+                ));
+                for ch in &rel.children {
+                    walk(ch, level + 1, count, lines);
+                }
+            }
+            RenderedEl::Container(cont) => {
+                for ch in cont {
+                    walk(ch, level, count, lines);
+                }
             }
         }
-        RenderedEl::Container(cont) => {
-            for ch in cont {
-                draw_element(ch, level);
+    }
+
+    let mut lines = Vec::new();
+    walk(e, 0, &mut 0, &mut lines);
+    lines
+}
+
+// Actionable nodes of the graph, in the order they are numbered
+pub fn actionable_nodes(e: &RenderedEl<TextNode>) -> Vec<Rc<TextNode>> {
+    fn walk(e: &RenderedEl<TextNode>, nodes: &mut Vec<Rc<TextNode>>) {
+        match e {
+            RenderedEl::None => {}
+            RenderedEl::Node(rel) => {
+                if rel.payload.is_actionable() {
+                    nodes.push(Rc::clone(&rel.payload));
+                }
+                rel.children.iter().for_each(|ch| walk(ch, nodes));
             }
+            RenderedEl::Container(cont) => cont.iter().for_each(|ch| walk(ch, nodes)),
         }
     }
+
+    let mut nodes = Vec::new();
+    walk(e, &mut nodes);
+    nodes
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    // "click 3", or just "3"
+    Click(usize),
+    // "input 3 some text"
+    Input(usize, String),
+    // prints the commands
+    Help,
+    Quit,
+    // nothing typed: renders again
+    Refresh,
+    Unknown(String),
+}
+
+pub fn parse_command(line: &str) -> Command {
+    let line = line.trim();
+    let mut words = line.splitn(2, char::is_whitespace);
+    let name = words.next().unwrap_or("");
+    let rest = words.next().unwrap_or("").trim_start();
+
+    let number = |s: &str| s.parse::<usize>().ok();
+    match name {
+        "" => Command::Refresh,
+        "help" | "?" => Command::Help,
+        "quit" | "q" | "exit" => Command::Quit,
+        "click" | "c" => match number(rest) {
+            Some(n) => Command::Click(n),
+            None => Command::Unknown(line.to_string()),
+        },
+        "input" | "i" => {
+            let mut args = rest.splitn(2, char::is_whitespace);
+            match args.next().and_then(number) {
+                Some(n) => Command::Input(n, args.next().unwrap_or("").to_string()),
+                None => Command::Unknown(line.to_string()),
+            }
+        }
+        n => match number(n) {
+            Some(n) if rest.is_empty() => Command::Click(n),
+            _ => Command::Unknown(line.to_string()),
+        },
+    }
+}
+
+const HELP: &str = "Commands: click N (or just N), input N <text>, help, quit";
+
+// Calls the handler of the node a command refers to. Help, quit and refresh
+// refer to no node: the loop running the commands handles them.
+pub fn dispatch_command(e: &RenderedEl<TextNode>, command: &Command) -> Result<(), String> {
+    let nodes = actionable_nodes(e);
+    let node = |n: usize| {
+        n.checked_sub(1)
+            .and_then(|i| nodes.get(i))
+            .ok_or(format!("No action [{}]", n))
+    };
+
+    match command {
+        Command::Click(n) => match &node(*n)?.on_click {
+            Some(h) => {
+                h();
+                Ok(())
+            }
+            None => Err(format!("[{}] is an input: input {} <text>", n, n)),
+        },
+        Command::Input(n, text) => match &node(*n)?.on_input {
+            Some(h) => {
+                h(text.clone());
+                Ok(())
+            }
+            None => Err(format!("[{}] takes no input: click {}", n, n)),
+        },
+        Command::Unknown(line) => Err(format!("Unknown command: {}. {}", line, HELP)),
+        Command::Help | Command::Quit | Command::Refresh => Ok(()),
+    }
+}
+
+enum Input {
+    Line(String),
+    // an update was posted by another thread
+    Wake,
+    // end of stdin
+    Closed,
+}
+
+// Line mode UI: prints the numbered graph, then reads commands from stdin.
+// The graph is printed again after each command, and whenever scheduled work
+// changes a state. Returns when stdin is closed, or on quit.
+pub fn run_interactive(app: impl Fn() -> El<TextNode>, state_store: Rc<RefCell<StateStore>>) {
+    // stdin is read on its own thread, so that timers still run while waiting
+    let (sender, inputs) = channel();
+    let waker = sender.clone();
+    state_store
+        .borrow()
+        .scheduler()
+        .borrow()
+        .set_waker(move || {
+            let _ = waker.send(Input::Wake);
+        });
+    thread::spawn(move || {
+        for line in io::stdin().lock().lines().map_while(Result::ok) {
+            if sender.send(Input::Line(line)).is_err() {
+                return;
+            }
+        }
+        let _ = sender.send(Input::Closed);
+    });
+
+    let mut graph = render_app_to_graph(&app(), Rc::clone(&state_store));
+    print_prompt(&graph, None);
+
+    loop {
+        let next_due = state_store.borrow().scheduler().borrow().next_due();
+        let received = match next_due {
+            None => inputs.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            Some(due) => inputs.recv_timeout(due.saturating_duration_since(Instant::now())),
+        };
+
+        let (typed, feedback) = match received {
+            Ok(Input::Line(line)) => match parse_command(&line) {
+                Command::Quit => break,
+                Command::Help => (true, Some(HELP.to_string())),
+                command => (true, dispatch_command(&graph, &command).err()),
+            },
+            Ok(Input::Wake) | Err(RecvTimeoutError::Timeout) => (false, None),
+            Ok(Input::Closed) | Err(RecvTimeoutError::Disconnected) => break,
+        };
+
+        let scheduled = run_scheduled(&state_store);
+        let changed = state_store.borrow().has_changed();
+        if typed || scheduled || changed {
+            graph = render_app_to_graph(&app(), Rc::clone(&state_store));
+            print_prompt(&graph, feedback);
+        }
+    }
+
+    state_store.borrow().scheduler().borrow().set_waker(|| {});
+}
+
+fn print_prompt(e: &RenderedEl<TextNode>, feedback: Option<String>) {
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    let _ = writeln!(out);
+    for line in numbered_lines(e) {
+        let _ = writeln!(out, "{}", line);
+    }
+    if let Some(feedback) = feedback {
+        let _ = writeln!(out, "{}", feedback);
+    }
+    let _ = write!(out, "> ");
+    let _ = out.flush();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(lines: &[&str]) -> RenderedEl<TextNode> {
+        let el = El::Container(lines.iter().map(|l| El::Node(node(l))).collect());
+        render_app_to_graph(&el, new_state_store())
+    }
+
+    // Output of a writer drawing the frames one after the other
+    fn output(mode: OutputMode, frames: &[&[&str]]) -> String {
+        let mut writer = TextWriter::new(Vec::new(), mode);
+        for lines in frames {
+            writer.draw(&graph(lines)).unwrap();
+        }
+        String::from_utf8(writer.into_inner()).unwrap()
+    }
+
+    #[test]
+    fn commands_are_parsed() {
+        assert_eq!(parse_command("  "), Command::Refresh);
+        assert_eq!(parse_command("help"), Command::Help);
+        assert_eq!(parse_command("?"), Command::Help);
+        assert_eq!(parse_command("q"), Command::Quit);
+        assert_eq!(parse_command("click 3"), Command::Click(3));
+        assert_eq!(parse_command(" c  12 "), Command::Click(12));
+        assert_eq!(parse_command("4"), Command::Click(4));
+        assert_eq!(
+            parse_command("input 2 hello  world"),
+            Command::Input(2, "hello  world".to_string())
+        );
+        assert_eq!(parse_command("i 2"), Command::Input(2, "".to_string()));
+    }
+
+    #[test]
+    fn invalid_commands_are_unknown() {
+        for line in [
+            "click", "click x", "input", "i x text", "4 5", "-1", "jump 3",
+        ]
+        .iter()
+        {
+            assert_eq!(parse_command(line), Command::Unknown(line.to_string()));
+        }
+    }
+
+    fn actionable_graph(clicked: Rc<RefCell<Vec<String>>>) -> RenderedEl<TextNode> {
+        let on_click = |name: &'static str| {
+            let clicked = Rc::clone(&clicked);
+            Some(Rc::new(move || clicked.borrow_mut().push(name.to_string())) as ClickHandler)
+        };
+        let typed = Rc::clone(&clicked);
+        let el = El::Node(node("form").add_children(vec![
+            El::Node(Node::new(TextNode::new("OK").set_on_click(on_click("ok")))),
+            El::Node(node("label")),
+            El::Node(Node::new(TextNode::new("name").set_on_input(Some(
+                Rc::new(move |text| typed.borrow_mut().push(text)),
+            )))),
+            El::Node(Node::new(
+                TextNode::new("Cancel").set_on_click(on_click("cancel")),
+            )),
+        ]));
+        render_app_to_graph(&el, new_state_store())
+    }
+
+    #[test]
+    fn commands_call_the_numbered_nodes() {
+        let called = Rc::new(RefCell::new(Vec::new()));
+        let graph = actionable_graph(Rc::clone(&called));
+        assert_eq!(
+            numbered_lines(&graph),
+            [
+                "form",
+                "    [1] OK",
+                "    label",
+                "    [2] name (input 2 <text>)",
+                "    [3] Cancel"
+            ]
+        );
+
+        assert_eq!(dispatch_command(&graph, &Command::Click(3)), Ok(()));
+        assert_eq!(
+            dispatch_command(&graph, &Command::Input(2, "Ada".to_string())),
+            Ok(())
+        );
+        assert_eq!(dispatch_command(&graph, &Command::Click(1)), Ok(()));
+        assert_eq!(*called.borrow(), ["cancel", "Ada", "ok"]);
+    }
+
+    #[test]
+    fn commands_report_the_nodes_they_do_not_fit() {
+        let called = Rc::new(RefCell::new(Vec::new()));
+        let graph = actionable_graph(Rc::clone(&called));
+        let dispatch = |command| dispatch_command(&graph, &command).unwrap_err();
+        assert_eq!(dispatch(Command::Click(0)), "No action [0]");
+        assert_eq!(dispatch(Command::Click(4)), "No action [4]");
+        assert_eq!(
+            dispatch(Command::Click(2)),
+            "[2] is an input: input 2 <text>"
+        );
+        assert_eq!(
+            dispatch(Command::Input(1, "x".to_string())),
+            "[1] takes no input: click 1"
+        );
+        assert!(
+            dispatch(Command::Unknown("jump".to_string())).starts_with("Unknown command: jump.")
+        );
+        assert_eq!(dispatch_command(&graph, &Command::Help), Ok(()));
+        assert!(called.borrow().is_empty());
+    }
+
+    #[test]
+    fn render_to_string_indents_children() {
+        let el = El::Node(node("parent").add_child(El::Node(node("child"))));
+        let graph = render_app_to_graph(&el, new_state_store());
+        assert_eq!(render_to_string(&graph), "parent\n    child\n");
+    }
+
+    #[test]
+    fn changes_mode_writes_the_changed_lines() {
+        assert_eq!(
+            output(
+                OutputMode::Changes,
+                &[&["a", "b", "c"], &["a", "B", "c"], &["a", "B", "c", "d"]]
+            ),
+            concat!(
+                "--- frame 1 ---\na\nb\nc\n",
+                "--- frame 2 (changes) ---\n2: B\n",
+                "--- frame 3 (changes) ---\n4: d\n"
+            )
+        );
+    }
+
+    #[test]
+    fn changes_mode_reports_removed_lines() {
+        assert_eq!(
+            output(OutputMode::Changes, &[&["a", "b", "c"], &["x"]]),
+            "--- frame 1 ---\na\nb\nc\n--- frame 2 (changes) ---\n1: x\n2-3: removed\n"
+        );
+    }
+
+    #[test]
+    fn unchanged_frames_are_not_written() {
+        for mode in [OutputMode::Changes, OutputMode::Full] {
+            assert_eq!(
+                output(mode, &[&["a"], &["a"], &["b"]]),
+                match mode {
+                    OutputMode::Changes => "--- frame 1 ---\na\n--- frame 2 (changes) ---\n1: b\n",
+                    _ => "--- frame 1 ---\na\n--- frame 2 ---\nb\n",
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn full_mode_writes_whole_frames() {
+        assert_eq!(
+            output(OutputMode::Full, &[&["a", "b"], &["a", "c"]]),
+            "--- frame 1 ---\na\nb\n--- frame 2 ---\na\nc\n"
+        );
+    }
+
+    #[test]
+    fn clear_mode_clears_before_each_frame() {
+        assert_eq!(
+            output(OutputMode::Clear, &[&["a"], &["a"]]),
+            "\x1b[0;0H\x1b[2Ja\n\x1b[0;0H\x1b[2Ja\n"
+        );
+    }
 }
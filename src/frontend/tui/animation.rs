@@ -0,0 +1,346 @@
+use std::any::Any;
+use std::cell::Cell;
+use std::f64::consts::PI;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use crate::engine::*;
+use crate::frontend::tui::*;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Easing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+    EaseInCubic,
+    EaseOutCubic,
+    EaseInOutCubic,
+    EaseInOutSine,
+    // overshoots the target, then settles back
+    EaseOutBack,
+}
+
+impl Easing {
+    // Share of the change done at t, the share of the duration elapsed
+    pub fn apply(self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t,
+            Easing::EaseOut => t * (2.0 - t),
+            Easing::EaseInOut if t < 0.5 => 2.0 * t * t,
+            Easing::EaseInOut => -1.0 + (4.0 - 2.0 * t) * t,
+            Easing::EaseInCubic => t * t * t,
+            Easing::EaseOutCubic => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOutCubic if t < 0.5 => 4.0 * t * t * t,
+            Easing::EaseInOutCubic => 1.0 - (-2.0 * t + 2.0).powi(3) / 2.0,
+            Easing::EaseInOutSine => -((PI * t).cos() - 1.0) / 2.0,
+            Easing::EaseOutBack => {
+                let c1 = 1.70158;
+                let c3 = c1 + 1.0;
+                1.0 + c3 * (t - 1.0).powi(3) + c1 * (t - 1.0).powi(2)
+            }
+        }
+    }
+}
+
+struct FrameClockState {
+    origin: Instant,
+    now: Cell<Instant>,
+    requested: Cell<bool>,
+}
+
+// Time of the current frame, shared by everything rendered in it, and whether
+// an animation needs another frame. Ticked by VSync::wait.
+#[derive(Clone)]
+pub struct FrameClock {
+    state: Rc<FrameClockState>,
+}
+
+impl Default for FrameClock {
+    fn default() -> Self {
+        FrameClock::new()
+    }
+}
+
+impl FrameClock {
+    pub fn new() -> Self {
+        let now = Instant::now();
+        FrameClock {
+            state: Rc::new(FrameClockState {
+                origin: now,
+                now: Cell::new(now),
+                requested: Cell::new(false),
+            }),
+        }
+    }
+
+    pub fn tick(&self, now: Instant) {
+        self.state.now.set(now);
+    }
+
+    pub fn now(&self) -> Instant {
+        self.state.now.get()
+    }
+
+    // Time of the current frame since the clock was created
+    pub fn elapsed(&self) -> Duration {
+        self.now().duration_since(self.state.origin)
+    }
+
+    // Called while rendering an animation that is not over
+    pub fn request_frame(&self) {
+        self.state.requested.set(true);
+    }
+
+    pub fn is_frame_requested(&self) -> bool {
+        self.state.requested.get()
+    }
+
+    // Whether a frame was requested since the last call
+    pub fn take_frame_request(&self) -> bool {
+        self.state.requested.replace(false)
+    }
+}
+
+// Change of a value from one number to another over time
+#[derive(Clone, Copy, Debug)]
+pub struct Tween {
+    pub from: f64,
+    pub to: f64,
+    pub start: Instant,
+    pub duration: Duration,
+    pub easing: Easing,
+}
+
+impl Tween {
+    pub fn new(from: f64, to: f64, start: Instant, duration: Duration, easing: Easing) -> Self {
+        Tween {
+            from,
+            to,
+            start,
+            duration,
+            easing,
+        }
+    }
+
+    // Share of the duration elapsed at now, in 0..=1
+    pub fn progress(&self, now: Instant) -> f64 {
+        if self.duration.as_nanos() == 0 {
+            return 1.0;
+        }
+        let elapsed = now.saturating_duration_since(self.start);
+        (elapsed.as_secs_f64() / self.duration.as_secs_f64()).min(1.0)
+    }
+
+    pub fn value(&self, now: Instant) -> f64 {
+        self.from + (self.to - self.from) * self.easing.apply(self.progress(now))
+    }
+
+    pub fn is_done(&self, now: Instant) -> bool {
+        self.progress(now) >= 1.0
+    }
+
+    // Tween to another value, starting from the current one
+    pub fn retarget(&self, to: f64, now: Instant) -> Tween {
+        Tween {
+            from: self.value(now),
+            to,
+            start: now,
+            ..*self
+        }
+    }
+}
+
+pub struct AnimatedProps {
+    // the clock of the VSync of the render loop
+    pub clock: FrameClock,
+    // value animated to, whenever it changes
+    pub value: f64,
+    // None: the first render shows value without animating
+    pub from: Option<f64>,
+    pub duration: Duration,
+    pub easing: Easing,
+    // renders the content for the current value
+    pub content: Box<dyn Fn(f64) -> El<TUINode>>,
+}
+
+impl Default for AnimatedProps {
+    fn default() -> Self {
+        AnimatedProps {
+            clock: FrameClock::new(),
+            value: 0.0,
+            from: None,
+            duration: Duration::from_millis(300),
+            easing: Easing::EaseInOut,
+            content: Box::new(|_| El::None),
+        }
+    }
+}
+
+// Renders its content with a value eased towards value, requesting frames from
+// the clock until the value is reached
+pub struct Animated {
+    pub props: AnimatedProps,
+}
+
+// The tween is replaced in place while rendering, not with set_state: its
+// changes are neither recorded in the history nor shown by snapshots, and
+// travelling back in time keeps the current tween.
+#[derive(Clone)]
+struct AnimatedState {
+    // retargeted while rendering, without a new render
+    tween: Rc<Cell<Option<Tween>>>,
+}
+
+pub fn animated(props: AnimatedProps) -> El<TUINode> {
+    El::Component(Box::new(Animated { props }))
+}
+
+impl StateReceiver<AnimatedState> for Animated {}
+impl Component<TUINode> for Animated {
+    fn initial_state(&self) -> Rc<dyn Any> {
+        Rc::new(AnimatedState {
+            tween: Rc::new(Cell::new(None)),
+        })
+    }
+
+    fn render(&self, state: Rc<BoxedState>, _: Rc<SetState>) -> El<TUINode> {
+        let props = &self.props;
+        let state = self.must_receive_state(state);
+        let now = props.clock.now();
+
+        let tween = match state.tween.get() {
+            None => Tween::new(
+                props.from.unwrap_or(props.value),
+                props.value,
+                now,
+                props.duration,
+                props.easing,
+            ),
+            Some(t) if t.to != props.value => Tween {
+                duration: props.duration,
+                easing: props.easing,
+                ..t.retarget(props.value, now)
+            },
+            Some(t) => t,
+        };
+        state.tween.set(Some(tween));
+
+        if !tween.is_done(now) {
+            props.clock.request_frame();
+        }
+        (props.content)(tween.value(now))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::tui::widgets::testing::Harness;
+
+    const EASINGS: [Easing; 9] = [
+        Easing::Linear,
+        Easing::EaseIn,
+        Easing::EaseOut,
+        Easing::EaseInOut,
+        Easing::EaseInCubic,
+        Easing::EaseOutCubic,
+        Easing::EaseInOutCubic,
+        Easing::EaseInOutSine,
+        Easing::EaseOutBack,
+    ];
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn easings_go_from_0_to_1() {
+        for easing in EASINGS.iter() {
+            assert_close(easing.apply(0.0), 0.0);
+            assert_close(easing.apply(1.0), 1.0);
+            // clamped
+            assert_close(easing.apply(-1.0), 0.0);
+            assert_close(easing.apply(2.0), 1.0);
+        }
+        assert!(Easing::EaseOutBack.apply(0.8) > 1.0);
+    }
+
+    #[test]
+    fn retargeting_starts_from_the_current_value() {
+        let start = Instant::now();
+        let tween = Tween::new(0.0, 10.0, start, Duration::from_millis(100), Easing::Linear);
+        let now = start + Duration::from_millis(50);
+        assert_close(tween.value(now), 5.0);
+
+        let tween = tween.retarget(20.0, now);
+        assert_close(tween.value(now), 5.0);
+        assert_close(tween.value(now + Duration::from_millis(50)), 12.5);
+        assert_close(tween.value(now + Duration::from_millis(100)), 20.0);
+        assert!(tween.is_done(now + Duration::from_millis(100)));
+    }
+
+    #[test]
+    fn instant_tweens_are_done() {
+        let start = Instant::now();
+        let tween = Tween::new(0.0, 10.0, start, Duration::ZERO, Easing::EaseInOut);
+        assert_eq!(tween.progress(start), 1.0);
+        assert_close(tween.value(start), 10.0);
+        assert!(tween.is_done(start));
+    }
+
+    fn harness(clock: FrameClock, value: Rc<Cell<f64>>, duration: Duration) -> Harness {
+        Harness::new(move || {
+            animated(AnimatedProps {
+                clock: clock.clone(),
+                value: value.get(),
+                from: Some(0.0),
+                duration,
+                easing: Easing::Linear,
+                content: Box::new(|v| {
+                    El::Node(Node::new(TUINode::new(0, 0).set_text(Some(v.to_string()))))
+                }),
+            })
+        })
+    }
+
+    #[test]
+    fn values_are_animated_until_reached() {
+        let clock = FrameClock::new();
+        let value = Rc::new(Cell::new(10.0));
+        let mut h = harness(clock.clone(), value.clone(), Duration::from_millis(100));
+        let origin = clock.now();
+        assert_eq!(h.texts(), ["0"]);
+        assert!(clock.take_frame_request());
+
+        clock.tick(origin + Duration::from_millis(50));
+        h.render();
+        assert_eq!(h.texts(), ["5"]);
+        assert!(clock.take_frame_request());
+
+        // retargeted mid-flight
+        value.set(25.0);
+        h.render();
+        assert_eq!(h.texts(), ["5"]);
+        assert!(clock.take_frame_request());
+        clock.tick(origin + Duration::from_millis(150));
+        h.render();
+        assert_eq!(h.texts(), ["25"]);
+        assert!(!clock.take_frame_request());
+    }
+
+    #[test]
+    fn instant_animations_need_no_frames() {
+        let clock = FrameClock::new();
+        let h = harness(clock.clone(), Rc::new(Cell::new(10.0)), Duration::ZERO);
+        assert_eq!(h.texts(), ["10"]);
+        assert!(!clock.is_frame_requested());
+    }
+}
@@ -0,0 +1,416 @@
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use termion::event::Key;
+
+use crate::engine::*;
+use crate::frontend::tui::overlay::*;
+use crate::frontend::tui::widgets::*;
+use crate::frontend::tui::*;
+
+// A node of the last graph drawn, where it was drawn
+#[derive(Clone, Default)]
+pub struct InspectedNode {
+    pub path: String,
+    // screen position, once scrolled
+    pub left: i32,
+    pub top: i32,
+    pub dim: Dimension,
+    pub z_index: i32,
+    pub border: bool,
+    pub clip: bool,
+    pub fill: bool,
+    pub inverse: bool,
+    pub portal: bool,
+    pub focusable: bool,
+    pub disabled: bool,
+    pub text: Option<String>,
+}
+
+// What the inspector shows of the last graph drawn
+#[derive(Clone, Default)]
+pub struct Inspection {
+    pub node_count: usize,
+    // topmost node under the mouse
+    pub hovered: Option<InspectedNode>,
+}
+
+// The nodes of the inspector itself are left out
+pub fn inspect_graph(graph: &RenderedEl<TUINode>, mouse: Option<(i32, i32)>) -> Inspection {
+    let layers: Vec<Layer> = stack_layers(graph)
+        .into_iter()
+        .filter(|l| !is_inspector_path(&l.node.path))
+        .collect();
+
+    let hovered = mouse.and_then(|(left, top)| {
+        layers
+            .iter()
+            .rev()
+            .find(|l| l.is_under_mouse(left, top))
+            .map(|l| {
+                let node = &l.node.payload;
+                let (left, top) = l.vp.screen_pos(node);
+                InspectedNode {
+                    path: l.node.path.clone(),
+                    left,
+                    top,
+                    dim: node.dim,
+                    z_index: l.z_index,
+                    border: node.style.border,
+                    clip: node.style.clip,
+                    fill: node.style.fill,
+                    inverse: node.style.inverse,
+                    portal: node.portal,
+                    focusable: node.focusable,
+                    disabled: l.disabled,
+                    text: node.text.clone(),
+                }
+            })
+    });
+
+    Inspection {
+        node_count: layers.len(),
+        hovered,
+    }
+}
+
+fn is_inspector_path(path: &str) -> bool {
+    path.contains(&format!("~{}", std::any::type_name::<Inspector>()))
+}
+
+pub struct InspectorProps {
+    pub pos: Position,
+    pub dim: Dimension,
+    // of the graph drawn last, with the current mouse position
+    pub inspection: Inspection,
+    pub state_store: Rc<RefCell<StateStore>>,
+    // shows the states of the registered components
+    pub registry: Option<Rc<StateRegistry>>,
+    // opens and closes the inspector, unless a focused node consumes it
+    pub toggle_key: Key,
+}
+
+impl InspectorProps {
+    // The inspector shows the states of the store of the app: there is no default
+    pub fn new(state_store: Rc<RefCell<StateStore>>) -> Self {
+        InspectorProps {
+            pos: Position { left: 1, top: 1 },
+            dim: Dimension {
+                width: 60,
+                height: 16,
+            },
+            inspection: Default::default(),
+            state_store,
+            registry: None,
+            toggle_key: Key::Char('i'),
+        }
+    }
+}
+
+// While open, the mouse picks the node to inspect instead of reaching the app:
+// the node under it is outlined, and the panel shows its box, its style, the
+// state of the component rendering it, and the tree of the mounted components.
+pub struct Inspector {
+    pub props: InspectorProps,
+}
+
+#[derive(Clone)]
+struct InspectorState {
+    // not a state of the store, to stay out of the history
+    open: Rc<Cell<bool>>,
+}
+
+pub fn inspector(props: InspectorProps) -> El<TUINode> {
+    El::Component(Box::new(Inspector { props }))
+}
+
+impl StateReceiver<InspectorState> for Inspector {}
+impl Component<TUINode> for Inspector {
+    fn initial_state(&self) -> Rc<dyn Any> {
+        Rc::new(InspectorState {
+            open: Rc::new(Cell::new(false)),
+        })
+    }
+
+    fn render(&self, state: Rc<BoxedState>, _: Rc<SetState>) -> El<TUINode> {
+        let props = &self.props;
+        let state = self.must_receive_state(state);
+
+        let on_key = handle_on_key(
+            Rc::clone(&props.state_store),
+            Rc::clone(&state.open),
+            props.toggle_key,
+        );
+        let listener = El::Node(Node::new(
+            TUINode::new(1, 1)
+                .set_dimension(0, 0)
+                .set_on_key(Some(on_key)),
+        ));
+        if !state.open.get() {
+            return listener;
+        }
+
+        let (width, height) = termion::terminal_size().unwrap_or((80, 24));
+        let picker = El::Node(Node::new(
+            TUINode::new(1, 1)
+                .set_dimension(width, height)
+                .set_on_mouse_move(Some(handle_on_mouse_move(Rc::clone(&props.state_store)))),
+        ));
+
+        El::Container(vec![
+            listener,
+            overlay(El::Container(vec![
+                picker,
+                self.render_outline(),
+                self.render_panel(),
+            ])),
+        ])
+    }
+}
+
+impl Inspector {
+    // Box drawn around the hovered node
+    fn render_outline(&self) -> El<TUINode> {
+        let node = match &self.props.inspection.hovered {
+            Some(n) => n,
+            None => return El::None,
+        };
+        let left = (node.left - 1).max(1) as u16;
+        let top = (node.top - 1).max(1) as u16;
+        let right = node.left + i32::from(node.dim.width);
+        let bottom = node.top + i32::from(node.dim.height);
+        El::Node(Node::new(
+            TUINode::new(left, top)
+                .set_dimension(
+                    (right - i32::from(left) + 1).max(1) as u16,
+                    (bottom - i32::from(top) + 1).max(1) as u16,
+                )
+                .set_border(true),
+        ))
+    }
+
+    fn render_panel(&self) -> El<TUINode> {
+        let props = &self.props;
+        let store = props.state_store.borrow();
+        let width = props.dim.width.saturating_sub(4) as usize;
+        let mut lines: Vec<(String, bool)> = vec![(
+            format!("Inspector   {} nodes", props.inspection.node_count),
+            false,
+        )];
+
+        let hovered = props.inspection.hovered.as_ref();
+        match hovered {
+            None => lines.push(("Move the mouse over a node".to_string(), false)),
+            Some(node) => {
+                lines.push((
+                    format!(
+                        "Node  {}",
+                        truncate_start(&node.path, width.saturating_sub(6))
+                    ),
+                    false,
+                ));
+                lines.push((
+                    format!(
+                        "      pos {},{}  dim {}x{}  z {}",
+                        node.left, node.top, node.dim.width, node.dim.height, node.z_index
+                    ),
+                    false,
+                ));
+                lines.push((format!("      {}", flags(node)), false));
+                if let Some(text) = &node.text {
+                    lines.push((format!("      text {:?}", text), false));
+                }
+            }
+        }
+
+        // components, parents first
+        let mut components: Vec<&str> = store.mounted().filter(|p| !is_inspector_path(p)).collect();
+        components.sort_unstable();
+        let is_ancestor = |component: &str| {
+            hovered.is_some_and(|n| {
+                n.path.starts_with(component) && n.path[component.len()..].starts_with('/')
+            })
+        };
+
+        // type and state of the components whose states are registered
+        let describe = |component: &str| {
+            let state = store.get(component)?;
+            props.registry.as_ref()?.describe(component, state.as_ref())
+        };
+
+        if let Some(owner) = components.iter().rev().find(|c| is_ancestor(c)) {
+            let state = match store.get(owner) {
+                None => "(none)".to_string(),
+                Some(_) => describe(owner).unwrap_or_else(|| "(state)".to_string()),
+            };
+            lines.push((format!("State {}", state), false));
+        }
+        lines.push(("Components".to_string(), false));
+        let tree_start = lines.len();
+        for c in &components {
+            let depth = component_depth(c);
+            let label = describe(c).unwrap_or_else(|| component_label(c).to_string());
+            lines.push((format!("{}{}", "  ".repeat(depth), label), is_ancestor(c)));
+        }
+
+        // the tree scrolls to keep the innermost component of the hovered node in view
+        let rows = props.dim.height.saturating_sub(2) as usize;
+        let last_ancestor = lines.iter().rposition(|(_, a)| *a).unwrap_or(0);
+        let skip = if lines.len() > rows && last_ancestor >= rows {
+            (last_ancestor + 1 - rows).min(lines.len() - rows)
+        } else {
+            0
+        };
+        let shown = lines
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i < tree_start || *i >= tree_start + skip)
+            .take(rows);
+
+        let (left, top) = (props.pos.left, props.pos.top);
+        El::Node(
+            Node::new(
+                TUINode::new(left, top)
+                    .set_dimension(props.dim.width, props.dim.height)
+                    .set_border(true)
+                    .set_fill(true),
+            )
+            .add_children(
+                shown
+                    .enumerate()
+                    .map(|(row, (_, (text, highlighted)))| {
+                        El::Node(Node::new(
+                            TUINode::new(left + 2, top + 1 + row as u16)
+                                .set_text(Some(align_text(text, width, Align::Left)))
+                                .set_inverse(*highlighted),
+                        ))
+                    })
+                    .collect(),
+            ),
+        )
+    }
+}
+
+fn flags(node: &InspectedNode) -> String {
+    let flags: Vec<&str> = [
+        (node.border, "border"),
+        (node.clip, "clip"),
+        (node.fill, "fill"),
+        (node.inverse, "inverse"),
+        (node.portal, "portal"),
+        (node.focusable, "focusable"),
+        (node.disabled, "disabled"),
+    ]
+    .iter()
+    .filter(|(set, _)| *set)
+    .map(|(_, name)| *name)
+    .collect();
+    match flags.is_empty() {
+        true => "no style".to_string(),
+        false => flags.join(" "),
+    }
+}
+
+// Segments of a path rendered by components, e.g. "0~app::Counter"
+fn is_component_segment(segment: &str) -> bool {
+    !segment.is_empty()
+        && !["~Node", "~Container", "~ErrorBoundary"]
+            .iter()
+            .any(|s| segment.ends_with(s))
+}
+
+fn component_depth(path: &str) -> usize {
+    path.split('/')
+        .filter(|s| is_component_segment(s))
+        .count()
+        .saturating_sub(1)
+}
+
+// Type name of the last component of the path, without its module
+fn component_label(path: &str) -> &str {
+    let segment = path.rsplit('/').next().unwrap_or(path);
+    short_type_name(segment.split_once('~').map_or(segment, |(_, t)| t))
+}
+
+fn handle_on_key(
+    state_store: Rc<RefCell<StateStore>>,
+    open: Rc<Cell<bool>>,
+    toggle_key: Key,
+) -> KeyHandler {
+    Rc::new(move |event: &mut KeyEvent| {
+        if event.key != toggle_key {
+            return None;
+        }
+        open.set(!open.get());
+        event.stop_propagation();
+        state_store.borrow_mut().request_render();
+        None
+    })
+}
+
+// The inspection follows the mouse
+fn handle_on_mouse_move(state_store: Rc<RefCell<StateStore>>) -> MouseHandler {
+    Rc::new(move |_: &mut UIEvent| {
+        state_store.borrow_mut().request_render();
+        None
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::tui::widgets::testing::Harness;
+
+    struct Counter {}
+
+    struct Plain {}
+
+    impl Component<TUINode> for Counter {
+        fn initial_state(&self) -> Rc<dyn Any> {
+            Rc::new(0)
+        }
+
+        fn render(&self, _: Rc<BoxedState>, _: Rc<SetState>) -> El<TUINode> {
+            El::Component(Box::new(Plain {}))
+        }
+    }
+
+    impl Component<TUINode> for Plain {
+        fn render(&self, _: Rc<BoxedState>, _: Rc<SetState>) -> El<TUINode> {
+            El::None
+        }
+    }
+
+    #[test]
+    fn component_trees_show_the_registered_states() {
+        let store = new_state_store();
+        render_app_to_graph(&El::Component(Box::new(Counter {})), store.clone());
+        let counter = format!("/0~{}", std::any::type_name::<Counter>());
+        store.borrow_mut().set(&counter, Rc::new(3));
+        let registry = Rc::new(
+            StateRegistry::new().register::<Counter, i32>(|n| n.to_string(), |s| s.parse().ok()),
+        );
+
+        let h = Harness::new(move || {
+            Inspector {
+                props: InspectorProps {
+                    registry: Some(registry.clone()),
+                    ..InspectorProps::new(store.clone())
+                },
+            }
+            .render_panel()
+        });
+        let lines: Vec<String> = h.texts().iter().map(|t| t.trim_end().to_string()).collect();
+        assert_eq!(
+            lines,
+            [
+                "Inspector   0 nodes",
+                "Move the mouse over a node",
+                "Components",
+                "Counter 3",
+                "  Plain",
+            ]
+        );
+    }
+}
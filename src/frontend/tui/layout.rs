@@ -0,0 +1,391 @@
+use std::any::Any;
+use std::rc::Rc;
+
+use termion::event::Key;
+
+use crate::engine::*;
+use crate::frontend::tui::widgets::{separator, ChangeHandler, Orientation, SeparatorProps};
+use crate::frontend::tui::*;
+
+// Renders the content of a pane, laid out in the box it is given
+pub type PaneContent = Box<dyn Fn(Position, Dimension) -> El<TUINode>>;
+
+pub struct Tab {
+    pub title: String,
+    pub content: PaneContent,
+}
+
+impl Tab {
+    pub fn new(
+        title: &str,
+        content: impl Fn(Position, Dimension) -> El<TUINode> + 'static,
+    ) -> Self {
+        Tab {
+            title: title.to_string(),
+            content: Box::new(content),
+        }
+    }
+}
+
+pub struct TabsProps {
+    pub pos: Position,
+    // tab bar and its underline included
+    pub dim: Dimension,
+    pub tabs: Vec<Tab>,
+    // tab shown on first render
+    pub selected: usize,
+    // called with the index of the tab switched to
+    pub on_change: Option<ChangeHandler<usize>>,
+}
+
+impl Default for TabsProps {
+    fn default() -> Self {
+        TabsProps {
+            pos: Position {
+                ..Default::default()
+            },
+            dim: Dimension {
+                ..Default::default()
+            },
+            tabs: Vec::new(),
+            selected: 0,
+            on_change: None,
+        }
+    }
+}
+
+// Tab bar over the content of the selected tab. Tabs are switched by a click
+// on their title, or with Left and Right once the bar is focused.
+pub struct Tabs {
+    pub props: TabsProps,
+}
+
+#[derive(Clone)]
+struct TabsState {
+    selected: usize,
+}
+
+pub fn tabs(props: TabsProps) -> El<TUINode> {
+    El::Component(Box::new(Tabs { props }))
+}
+
+impl StateReceiver<TabsState> for Tabs {}
+impl Component<TUINode> for Tabs {
+    fn initial_state(&self) -> Rc<dyn Any> {
+        Rc::new(TabsState {
+            selected: self.props.selected,
+        })
+    }
+
+    fn render(&self, state: Rc<BoxedState>, set_state: Rc<SetState>) -> El<TUINode> {
+        let props = &self.props;
+        let state = self.must_receive_state(state);
+        let count = props.tabs.len();
+        let selected = state.selected.min(count.saturating_sub(1));
+
+        let mut left = props.pos.left;
+        let titles = props
+            .tabs
+            .iter()
+            .enumerate()
+            .map(|(i, tab)| {
+                let text = format!(" {} ", tab.title);
+                let width = text.chars().count() as u16;
+                let node = TUINode::new(left, props.pos.top)
+                    .set_dimension(width, 1)
+                    .set_text(Some(text))
+                    .set_inverse(i == selected)
+                    .set_on_click(Some(handle_on_tab_click(
+                        i,
+                        set_state.clone(),
+                        props.on_change.clone(),
+                    )));
+                left = left.saturating_add(width + 1);
+                El::Node(Node::new(node))
+            })
+            .collect();
+
+        let bar = El::Node(
+            Node::new(
+                TUINode::new(props.pos.left, props.pos.top)
+                    .set_dimension(props.dim.width, 1)
+                    .set_focusable(true)
+                    .set_on_key(Some(handle_on_key(
+                        selected,
+                        count,
+                        set_state,
+                        props.on_change.clone(),
+                    ))),
+            )
+            .add_children(titles),
+        );
+
+        // one slot per tab, so that tabs do not share the state of their components
+        let panes = (0..count)
+            .map(|i| match i == selected {
+                true => (props.tabs[i].content)(
+                    Position {
+                        left: props.pos.left,
+                        top: props.pos.top + 2,
+                    },
+                    Dimension {
+                        width: props.dim.width,
+                        height: props.dim.height.saturating_sub(2),
+                    },
+                ),
+                false => El::None,
+            })
+            .collect();
+
+        El::Container(vec![
+            bar,
+            separator(SeparatorProps {
+                pos: Position {
+                    left: props.pos.left,
+                    top: props.pos.top + 1,
+                },
+                length: props.dim.width,
+                ..Default::default()
+            }),
+            El::Container(panes),
+        ])
+    }
+}
+
+fn switch_tab(
+    index: usize,
+    set_state: &Rc<SetState>,
+    on_change: &Option<ChangeHandler<usize>>,
+) -> Option<Message> {
+    set_state(Rc::new(TabsState { selected: index }));
+    on_change.as_ref().and_then(|h| h(index))
+}
+
+fn handle_on_tab_click(
+    index: usize,
+    set_state: Rc<SetState>,
+    on_change: Option<ChangeHandler<usize>>,
+) -> MouseHandler {
+    Rc::new(move |_: &mut UIEvent| switch_tab(index, &set_state, &on_change))
+}
+
+fn handle_on_key(
+    selected: usize,
+    count: usize,
+    set_state: Rc<SetState>,
+    on_change: Option<ChangeHandler<usize>>,
+) -> KeyHandler {
+    Rc::new(move |event: &mut KeyEvent| {
+        let index = match event.key {
+            Key::Left if selected > 0 => selected - 1,
+            Key::Right if selected + 1 < count => selected + 1,
+            Key::Home => 0,
+            Key::End => count.saturating_sub(1),
+            _ => return None,
+        };
+        event.stop_propagation();
+        switch_tab(index, &set_state, &on_change)
+    })
+}
+
+pub struct SplitPaneProps {
+    pub pos: Position,
+    pub dim: Dimension,
+    // Horizontal: panes side by side, split by a vertical divider;
+    // Vertical: panes on top of each other
+    pub orientation: Orientation,
+    // size of the first pane on first render, along the orientation
+    pub split: u16,
+    pub min_first: u16,
+    pub min_second: u16,
+    pub first: PaneContent,
+    pub second: PaneContent,
+}
+
+impl Default for SplitPaneProps {
+    fn default() -> Self {
+        SplitPaneProps {
+            pos: Position {
+                ..Default::default()
+            },
+            dim: Dimension {
+                ..Default::default()
+            },
+            orientation: Orientation::Horizontal,
+            split: 0,
+            min_first: 1,
+            min_second: 1,
+            first: Box::new(|_, _| El::None),
+            second: Box::new(|_, _| El::None),
+        }
+    }
+}
+
+// Two panes split by a divider that is moved by dragging it
+pub struct SplitPane {
+    pub props: SplitPaneProps,
+}
+
+#[derive(Clone)]
+struct SplitPaneState {
+    split: u16,
+}
+
+pub fn split_pane(props: SplitPaneProps) -> El<TUINode> {
+    El::Component(Box::new(SplitPane { props }))
+}
+
+impl SplitPane {
+    // Size of the box along the orientation
+    fn length(&self) -> u16 {
+        match self.props.orientation {
+            Orientation::Horizontal => self.props.dim.width,
+            Orientation::Vertical => self.props.dim.height,
+        }
+    }
+
+    // Keeps both panes above their minimum size, the first one winning if
+    // the box is too small for both
+    fn clamp_split(&self, split: i32) -> u16 {
+        let max = i32::from(self.length()) - 1 - i32::from(self.props.min_second);
+        split.min(max).max(i32::from(self.props.min_first)).max(0) as u16
+    }
+}
+
+impl StateReceiver<SplitPaneState> for SplitPane {}
+impl Component<TUINode> for SplitPane {
+    fn initial_state(&self) -> Rc<dyn Any> {
+        Rc::new(SplitPaneState {
+            split: self.props.split,
+        })
+    }
+
+    fn render(&self, state: Rc<BoxedState>, set_state: Rc<SetState>) -> El<TUINode> {
+        let props = &self.props;
+        let state = self.must_receive_state(state);
+        let split = self.clamp_split(state.split.into());
+        let second = self.length().saturating_sub(split + 1);
+
+        let (left, top, width, height) = (
+            props.pos.left,
+            props.pos.top,
+            props.dim.width,
+            props.dim.height,
+        );
+        let (first_box, divider_pos, divider_dim, second_box) = match props.orientation {
+            Orientation::Horizontal => (
+                (
+                    Position { left, top },
+                    Dimension {
+                        width: split,
+                        height,
+                    },
+                ),
+                Position {
+                    left: left + split,
+                    top,
+                },
+                Dimension { width: 1, height },
+                (
+                    Position {
+                        left: left + split + 1,
+                        top,
+                    },
+                    Dimension {
+                        width: second,
+                        height,
+                    },
+                ),
+            ),
+            Orientation::Vertical => (
+                (
+                    Position { left, top },
+                    Dimension {
+                        width,
+                        height: split,
+                    },
+                ),
+                Position {
+                    left,
+                    top: top + split,
+                },
+                Dimension { width, height: 1 },
+                (
+                    Position {
+                        left,
+                        top: top + split + 1,
+                    },
+                    Dimension {
+                        width,
+                        height: second,
+                    },
+                ),
+            ),
+        };
+
+        // the divider is drawn across the panes, so along the other orientation
+        let divider_orientation = match props.orientation {
+            Orientation::Horizontal => Orientation::Vertical,
+            Orientation::Vertical => Orientation::Horizontal,
+        };
+        let divider = El::Node(
+            Node::new(
+                TUINode::new(divider_pos.left, divider_pos.top)
+                    .set_dimension(divider_dim.width, divider_dim.height)
+                    .set_on_mouse_move(Some(handle_on_divider_drag(
+                        split,
+                        props.orientation,
+                        set_state,
+                    ))),
+            )
+            .add_child(separator(SeparatorProps {
+                pos: divider_pos,
+                length: match props.orientation {
+                    Orientation::Horizontal => height,
+                    Orientation::Vertical => width,
+                },
+                orientation: divider_orientation,
+            })),
+        );
+
+        El::Container(vec![
+            clipped_pane(first_box.0, first_box.1, &props.first),
+            divider,
+            clipped_pane(second_box.0, second_box.1, &props.second),
+        ])
+    }
+}
+
+fn clipped_pane(pos: Position, dim: Dimension, content: &PaneContent) -> El<TUINode> {
+    El::Node(
+        Node::new(
+            TUINode::new(pos.left, pos.top)
+                .set_dimension(dim.width, dim.height)
+                .set_clip(true),
+        )
+        .add_child(content(pos, dim)),
+    )
+}
+
+fn handle_on_divider_drag(
+    split: u16,
+    orientation: Orientation,
+    set_state: Rc<SetState>,
+) -> MouseHandler {
+    Rc::new(move |event: &mut UIEvent| {
+        if event.action != MouseAction::Drag {
+            return None;
+        }
+        // the mouse position is relative to the divider where it was rendered
+        let delta = match orientation {
+            Orientation::Horizontal => event.left,
+            Orientation::Vertical => event.top,
+        };
+        if delta != 0 {
+            // clamped on render, where the sizes are known
+            let split = (i32::from(split) + delta).clamp(0, i32::from(u16::MAX)) as u16;
+            set_state(Rc::new(SplitPaneState { split }));
+        }
+        None
+    })
+}
@@ -0,0 +1,257 @@
+use std::time::{Duration, Instant};
+
+use crate::engine::*;
+use crate::frontend::tui::overlay::*;
+use crate::frontend::tui::widgets::*;
+use crate::frontend::tui::*;
+
+// Measures of the last frame, and totals since the first one
+#[derive(Clone, Debug, Default)]
+pub struct FrameMetrics {
+    pub frames: u64,
+    pub render: RenderMetrics,
+    pub draw: DrawMetrics,
+    // since the previous frame
+    pub interval: Option<Duration>,
+    // the VSync target
+    pub target: Duration,
+    // frame slots missed because rendering and drawing took longer than the
+    // target: each frame adds the number of whole targets its frame_time spans,
+    // e.g. 2 for a frame of 2.5 targets, 0 for one within its target. The time
+    // waited between frames is not counted.
+    pub dropped: u64,
+    pub bytes_total: u64,
+}
+
+impl FrameMetrics {
+    // Render, layout and draw
+    pub fn frame_time(&self) -> Duration {
+        self.render.duration + self.draw.layout + self.draw.draw
+    }
+
+    pub fn fps(&self) -> f64 {
+        match self.interval {
+            Some(i) if i.as_secs_f64() > 0.0 => 1.0 / i.as_secs_f64(),
+            _ => 0.0,
+        }
+    }
+}
+
+// Collects the metrics of the frames drawn: requires the metrics of the state
+// store to be enabled
+pub struct Profiler {
+    metrics: FrameMetrics,
+    last_frame: Option<Instant>,
+}
+
+impl Profiler {
+    pub fn new(target: Duration) -> Self {
+        Profiler {
+            metrics: FrameMetrics {
+                target,
+                ..Default::default()
+            },
+            last_frame: None,
+        }
+    }
+
+    // Called once the frame is drawn
+    pub fn record(&mut self, render: Option<&RenderMetrics>, draw: DrawMetrics) {
+        let now = Instant::now();
+        let m = &mut self.metrics;
+        m.frames += 1;
+        m.render = render.cloned().unwrap_or_default();
+        m.draw = draw;
+        m.interval = self.last_frame.map(|t| now.duration_since(t));
+        m.bytes_total += draw.bytes as u64;
+        // no target: no frame slots to miss
+        if m.target.as_nanos() > 0 {
+            m.dropped += (m.frame_time().as_nanos() / m.target.as_nanos()) as u64;
+        }
+        self.last_frame = Some(now);
+    }
+
+    pub fn metrics(&self) -> &FrameMetrics {
+        &self.metrics
+    }
+}
+
+pub struct HudProps {
+    pub pos: Position,
+    pub width: u16,
+    pub metrics: FrameMetrics,
+    // slowest component types listed
+    pub components: usize,
+}
+
+impl Default for HudProps {
+    fn default() -> Self {
+        HudProps {
+            pos: Position { left: 1, top: 1 },
+            width: 48,
+            metrics: Default::default(),
+            components: 3,
+        }
+    }
+}
+
+fn millis(d: Duration) -> String {
+    format!("{:.2}ms", d.as_secs_f64() * 1000.0)
+}
+
+fn kilobytes(bytes: u64) -> String {
+    format!("{:.1}KB", bytes as f64 / 1024.0)
+}
+
+// Metrics of the last frame, in a box of the overlay layer
+pub fn hud(props: HudProps) -> El<TUINode> {
+    let m = &props.metrics;
+    let mut lines = vec![
+        format!(
+            "frame {}  {:.1} fps  dropped {}",
+            m.frames,
+            m.fps(),
+            m.dropped
+        ),
+        format!(
+            "render {}  layout {}  draw {}",
+            millis(m.render.duration),
+            millis(m.draw.layout),
+            millis(m.draw.draw)
+        ),
+        format!(
+            "total {} / {}  written {}",
+            millis(m.frame_time()),
+            millis(m.target),
+            kilobytes(m.draw.bytes as u64)
+        ),
+        format!(
+            "{} components  {} written in all",
+            m.render.component_count(),
+            kilobytes(m.bytes_total)
+        ),
+    ];
+    lines.extend(m.render.components.iter().take(props.components).map(|c| {
+        format!(
+            "  {} x{}  {}",
+            short_type_name(c.type_name),
+            c.count,
+            millis(c.duration)
+        )
+    }));
+
+    let (left, top) = (props.pos.left, props.pos.top);
+    let width = props.width.saturating_sub(4) as usize;
+    overlay(El::Node(
+        Node::new(
+            TUINode::new(left, top)
+                .set_dimension(props.width, lines.len() as u16 + 2)
+                .set_border(true)
+                .set_fill(true),
+        )
+        .add_children(
+            lines
+                .iter()
+                .enumerate()
+                .map(|(i, line)| {
+                    El::Node(Node::new(
+                        TUINode::new(left + 2, top + 1 + i as u16).set_text(Some(align_text(
+                            line,
+                            width,
+                            Align::Left,
+                        ))),
+                    ))
+                })
+                .collect(),
+        ),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::tui::widgets::testing::Harness;
+
+    fn render(duration: Duration) -> RenderMetrics {
+        RenderMetrics {
+            duration,
+            components: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn slow_frames_drop_the_slots_they_span() {
+        let mut profiler = Profiler::new(Duration::from_millis(10));
+        let draw = DrawMetrics {
+            layout: Duration::from_millis(1),
+            draw: Duration::from_millis(4),
+            bytes: 100,
+        };
+        profiler.record(Some(&render(Duration::from_millis(20))), draw);
+        let m = profiler.metrics();
+        assert_eq!((m.frames, m.dropped, m.bytes_total), (1, 2, 100));
+        assert_eq!(m.frame_time(), Duration::from_millis(25));
+        assert_eq!(m.interval, None);
+
+        profiler.record(Some(&render(Duration::from_millis(4))), draw);
+        let m = profiler.metrics();
+        assert_eq!((m.frames, m.dropped, m.bytes_total), (2, 2, 200));
+        assert!(m.interval.is_some());
+    }
+
+    #[test]
+    fn frames_without_target_drop_nothing() {
+        let mut profiler = Profiler::new(Duration::ZERO);
+        profiler.record(Some(&render(Duration::from_secs(1))), Default::default());
+        assert_eq!(profiler.metrics().dropped, 0);
+    }
+
+    #[test]
+    fn huds_show_the_last_frame() {
+        let metrics = FrameMetrics {
+            frames: 3,
+            render: RenderMetrics {
+                duration: Duration::from_millis(4),
+                components: vec![
+                    ComponentMetrics {
+                        type_name: "app::Counter",
+                        count: 2,
+                        duration: Duration::from_millis(3),
+                    },
+                    ComponentMetrics {
+                        type_name: "app::Label",
+                        count: 5,
+                        duration: Duration::from_millis(1),
+                    },
+                ],
+            },
+            draw: DrawMetrics {
+                layout: Duration::from_millis(1),
+                draw: Duration::from_millis(2),
+                bytes: 2048,
+            },
+            interval: Some(Duration::from_millis(20)),
+            target: Duration::from_millis(16),
+            dropped: 2,
+            bytes_total: 10240,
+        };
+        let h = Harness::new(move || {
+            hud(HudProps {
+                metrics: metrics.clone(),
+                components: 1,
+                ..Default::default()
+            })
+        });
+        let lines: Vec<String> = h.texts().iter().map(|t| t.trim_end().to_string()).collect();
+        assert_eq!(
+            lines,
+            [
+                "frame 3  50.0 fps  dropped 2",
+                "render 4.00ms  layout 1.00ms  draw 2.00ms",
+                "total 7.00ms / 16.00ms  written 2.0KB",
+                "7 components  10.0KB written in all",
+                "  Counter x2  3.00ms",
+            ]
+        );
+    }
+}
@@ -1,27 +1,39 @@
-use std::io::{Stdout, Write};
+use std::any::Any;
+use std::io::{self, Stdout, Write};
 use std::ops::Sub;
 use std::rc::Rc;
-use std::sync::Mutex;
 use std::time::Instant;
 use std::{thread::sleep, time::Duration};
 
 use termion::color;
 use termion::cursor;
 use termion::cursor::Goto;
-use termion::event::{Event, Key, MouseEvent};
-use termion::input::Events;
+use termion::event::{Event, Key, MouseButton, MouseEvent};
 use termion::raw::RawTerminal;
-use termion::AsyncReader;
+use termion::style;
 
 use crate::engine::*;
 
-#[derive(Default)]
+pub mod animation;
+pub mod inspector;
+pub mod layout;
+pub mod metrics;
+pub mod overlay;
+pub mod runtime;
+pub mod scroll;
+pub mod time_travel;
+pub mod widgets;
+
+// Stacking level of the nodes drawn in the overlay layer (see TUINode::portal)
+pub const OVERLAY_Z_INDEX: i32 = 1000;
+
+#[derive(Clone, Copy, Default)]
 pub struct Position {
     pub left: u16,
     pub top: u16,
 }
 
-#[derive(Default)]
+#[derive(Clone, Copy, Default)]
 pub struct Dimension {
     pub width: u16,
     pub height: u16,
@@ -30,19 +42,158 @@ pub struct Dimension {
 #[derive(Default)]
 pub struct Style {
     pub border: bool,
+    // children are not drawn outside of the node box
+    pub clip: bool,
+    // the box is cleared before drawing, hiding what is stacked below
+    pub fill: bool,
+    // text drawn with swapped foreground and background colors
+    pub inverse: bool,
 }
 
 #[derive(Default)]
 pub struct EventHandlers {
-    pub on_click: Option<MouseClickHandler>,
+    pub on_click: Option<MouseHandler>,
+    // called on the way down to the target, before the on_click handlers
+    pub on_click_capture: Option<MouseHandler>,
+    pub on_double_click: Option<MouseHandler>,
+    pub on_mouse_down: Option<MouseHandler>,
+    pub on_mouse_up: Option<MouseHandler>,
+    // mouse moved over the node, or dragged after a mouse down on the node
+    pub on_mouse_move: Option<MouseHandler>,
+    // do not bubble: called on every node entering or leaving the hovered path
+    pub on_mouse_enter: Option<MouseHandler>,
+    pub on_mouse_leave: Option<MouseHandler>,
+    pub on_wheel: Option<MouseHandler>,
+    pub on_key: Option<KeyHandler>,
+    // modal nodes only: click landing outside of the modal
+    pub on_click_outside: Option<MouseHandler>,
+}
+
+// Handlers may return a message for the application (see InputState::take_messages)
+pub type MouseHandler = Rc<dyn Fn(&mut UIEvent) -> Option<Message>>;
+pub type KeyHandler = Rc<dyn Fn(&mut KeyEvent) -> Option<Message>>;
+
+pub type Message = Box<dyn Any>;
+
+pub fn message<M: 'static>(msg: M) -> Option<Message> {
+    Some(Box::new(msg))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EventPhase {
+    // from the root down to the parent of the target
+    Capture,
+    Target,
+    // from the parent of the target up to the root
+    Bubble,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MouseAction {
+    Down(MouseButton),
+    Up,
+    Click,
+    DoubleClick,
+    Move,
+    // move while a button is held
+    Drag,
+    Enter,
+    Leave,
+    Wheel(WheelDirection),
 }
 
-pub type MouseClickHandler = Rc<Mutex<dyn Fn()>>;
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub alt: bool,
+    pub ctrl: bool,
+}
+
+// Event dispatched to the node under the mouse, through its ancestors
+pub struct UIEvent {
+    pub phase: EventPhase,
+    pub action: MouseAction,
+    pub modifiers: Modifiers,
+    // path of the node the event is dispatched to
+    pub target: String,
+    // path of the node whose handler is called
+    pub current_target: String,
+    // mouse position relative to the target box; (0, 0) is its top left cell
+    pub left: i32,
+    pub top: i32,
+    // mouse position on the screen; (1, 1) is the top left cell
+    pub screen_left: i32,
+    pub screen_top: i32,
+    propagation_stopped: bool,
+}
+
+impl UIEvent {
+    fn new(target: &Layer, action: MouseAction, pointer: &Pointer) -> Self {
+        let (target_left, target_top) = target.vp.screen_pos(&target.node.payload);
+        UIEvent {
+            phase: EventPhase::Target,
+            action,
+            modifiers: pointer.modifiers,
+            target: target.node.path.clone(),
+            current_target: target.node.path.clone(),
+            left: pointer.left - target_left,
+            top: pointer.top - target_top,
+            screen_left: pointer.left,
+            screen_top: pointer.top,
+            propagation_stopped: false,
+        }
+    }
+
+    // no further handler is called for this event
+    pub fn stop_propagation(&mut self) {
+        self.propagation_stopped = true;
+    }
+
+    pub fn is_propagation_stopped(&self) -> bool {
+        self.propagation_stopped
+    }
+}
+
+// Key offered to the key handlers, topmost node first
+pub struct KeyEvent {
+    pub key: Key,
+    // path of the node whose handler is called
+    pub current_target: String,
+    propagation_stopped: bool,
+}
+
+impl KeyEvent {
+    // the key is consumed: no further handler is called
+    pub fn stop_propagation(&mut self) {
+        self.propagation_stopped = true;
+    }
+
+    pub fn is_propagation_stopped(&self) -> bool {
+        self.propagation_stopped
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WheelDirection {
+    Up,
+    Down,
+}
 
 pub struct TUINode {
     pub pos: Position,
     pub dim: Dimension,
     pub style: Style,
+    // offset subtracted from the position of the children when drawn
+    pub scroll_offset: Position,
+    // stacking order relative to the parent; higher is drawn above
+    pub z_index: i32,
+    // drawn in the overlay layer, above the regular content and
+    // out of the clipping and scrolling of the ancestors
+    pub portal: bool,
+    // captures the input: nodes outside of its subtree receive no events
+    pub modal: bool,
+    // receives the key events first once focused by a mouse down
+    pub focusable: bool,
     pub text: Option<String>,
     pub disabled: bool,
     pub event_handlers: EventHandlers,
@@ -59,6 +210,11 @@ impl Default for TUINode {
             style: Style {
                 ..Default::default()
             },
+            scroll_offset: Position { left: 0, top: 0 },
+            z_index: 0,
+            portal: false,
+            modal: false,
+            focusable: false,
             disabled: false,
             text: None,
             event_handlers: EventHandlers {
@@ -87,6 +243,46 @@ impl TUINode {
         self
     }
 
+    pub fn set_clip(mut self, c: bool) -> Self {
+        self.style.clip = c;
+        self
+    }
+
+    pub fn set_fill(mut self, f: bool) -> Self {
+        self.style.fill = f;
+        self
+    }
+
+    pub fn set_z_index(mut self, z: i32) -> Self {
+        self.z_index = z;
+        self
+    }
+
+    pub fn set_portal(mut self, p: bool) -> Self {
+        self.portal = p;
+        self
+    }
+
+    pub fn set_modal(mut self, m: bool) -> Self {
+        self.modal = m;
+        self
+    }
+
+    pub fn set_focusable(mut self, f: bool) -> Self {
+        self.focusable = f;
+        self
+    }
+
+    pub fn set_inverse(mut self, i: bool) -> Self {
+        self.style.inverse = i;
+        self
+    }
+
+    pub fn set_scroll_offset(mut self, left: u16, top: u16) -> Self {
+        self.scroll_offset = Position { left, top };
+        self
+    }
+
     pub fn set_text(mut self, t: Option<String>) -> Self {
         self.text = t;
         self
@@ -97,179 +293,957 @@ impl TUINode {
         self
     }
 
-    pub fn set_on_click(mut self, handler: Option<MouseClickHandler>) -> Self {
+    pub fn set_on_click(mut self, handler: Option<MouseHandler>) -> Self {
         self.event_handlers.on_click = handler;
         self
     }
+
+    pub fn set_on_click_capture(mut self, handler: Option<MouseHandler>) -> Self {
+        self.event_handlers.on_click_capture = handler;
+        self
+    }
+
+    pub fn set_on_double_click(mut self, handler: Option<MouseHandler>) -> Self {
+        self.event_handlers.on_double_click = handler;
+        self
+    }
+
+    pub fn set_on_mouse_down(mut self, handler: Option<MouseHandler>) -> Self {
+        self.event_handlers.on_mouse_down = handler;
+        self
+    }
+
+    pub fn set_on_mouse_up(mut self, handler: Option<MouseHandler>) -> Self {
+        self.event_handlers.on_mouse_up = handler;
+        self
+    }
+
+    pub fn set_on_mouse_move(mut self, handler: Option<MouseHandler>) -> Self {
+        self.event_handlers.on_mouse_move = handler;
+        self
+    }
+
+    pub fn set_on_mouse_enter(mut self, handler: Option<MouseHandler>) -> Self {
+        self.event_handlers.on_mouse_enter = handler;
+        self
+    }
+
+    pub fn set_on_mouse_leave(mut self, handler: Option<MouseHandler>) -> Self {
+        self.event_handlers.on_mouse_leave = handler;
+        self
+    }
+
+    pub fn set_on_wheel(mut self, handler: Option<MouseHandler>) -> Self {
+        self.event_handlers.on_wheel = handler;
+        self
+    }
+
+    pub fn set_on_key(mut self, handler: Option<KeyHandler>) -> Self {
+        self.event_handlers.on_key = handler;
+        self
+    }
+
+    pub fn set_on_click_outside(mut self, handler: Option<MouseHandler>) -> Self {
+        self.event_handlers.on_click_outside = handler;
+        self
+    }
+
+    // Box available to the children of the node when clipping
+    fn inner_rect(&self, left: i32, top: i32) -> Rect {
+        let (width, height) = (i32::from(self.dim.width), i32::from(self.dim.height));
+        if self.style.border {
+            Rect {
+                left: left + 1,
+                top: top + 1,
+                width: width - 2,
+                height: height - 2,
+            }
+        } else {
+            Rect {
+                left,
+                top,
+                width,
+                height,
+            }
+        }
+    }
 }
 
-pub fn draw_graph(stdout: &mut RawTerminal<Stdout>, el: &RenderedEl<TUINode>) {
-    write!(stdout, "{}{}", termion::clear::All, cursor::Hide).unwrap();
-    draw_rendered(stdout, el);
-    stdout.flush().unwrap();
+// Serializer for dump_value, to_json and debug_dump; handlers are left out
+pub fn tui_payload(node: &TUINode) -> DumpValue {
+    DumpValue::object(vec![
+        (
+            "pos",
+            DumpValue::object(vec![
+                ("left", node.pos.left.into()),
+                ("top", node.pos.top.into()),
+            ]),
+        ),
+        (
+            "dim",
+            DumpValue::object(vec![
+                ("width", node.dim.width.into()),
+                ("height", node.dim.height.into()),
+            ]),
+        ),
+        (
+            "style",
+            DumpValue::object(vec![
+                ("border", node.style.border.into()),
+                ("clip", node.style.clip.into()),
+                ("fill", node.style.fill.into()),
+                ("inverse", node.style.inverse.into()),
+            ]),
+        ),
+        (
+            "scroll_offset",
+            DumpValue::object(vec![
+                ("left", node.scroll_offset.left.into()),
+                ("top", node.scroll_offset.top.into()),
+            ]),
+        ),
+        ("z_index", node.z_index.into()),
+        ("portal", node.portal.into()),
+        ("modal", node.modal.into()),
+        ("focusable", node.focusable.into()),
+        ("disabled", node.disabled.into()),
+        ("text", node.text.clone().into()),
+    ])
+}
+
+#[derive(Clone, Copy)]
+struct Rect {
+    left: i32,
+    top: i32,
+    width: i32,
+    height: i32,
 }
 
-fn draw_rendered(stdout: &mut RawTerminal<Stdout>, e: &RenderedEl<TUINode>) {
+impl Rect {
+    fn contains(&self, left: i32, top: i32) -> bool {
+        left >= self.left
+            && left < self.left + self.width
+            && top >= self.top
+            && top < self.top + self.height
+    }
+
+    fn intersect(&self, other: &Rect) -> Rect {
+        let left = self.left.max(other.left);
+        let top = self.top.max(other.top);
+        let right = (self.left + self.width).min(other.left + other.width);
+        let bottom = (self.top + self.height).min(other.top + other.height);
+        Rect {
+            left,
+            top,
+            width: (right - left).max(0),
+            height: (bottom - top).max(0),
+        }
+    }
+}
+
+// Screen area a node is drawn into: the clip box inherited from its clipping
+// ancestors, and the accumulated scroll offset of its scrolling ancestors.
+#[derive(Clone, Copy)]
+struct Viewport {
+    clip: Option<Rect>,
+    shift_left: i32,
+    shift_top: i32,
+}
+
+impl Viewport {
+    fn root() -> Self {
+        Viewport {
+            clip: None,
+            shift_left: 0,
+            shift_top: 0,
+        }
+    }
+
+    fn screen_pos(&self, node: &TUINode) -> (i32, i32) {
+        (
+            i32::from(node.pos.left) - self.shift_left,
+            i32::from(node.pos.top) - self.shift_top,
+        )
+    }
+
+    fn is_visible(&self, left: i32, top: i32) -> bool {
+        match &self.clip {
+            None => true,
+            Some(clip) => clip.contains(left, top),
+        }
+    }
+
+    fn for_children(&self, node: &TUINode) -> Viewport {
+        let mut vp = *self;
+        if node.style.clip {
+            let (left, top) = self.screen_pos(node);
+            let inner = node.inner_rect(left, top);
+            vp.clip = Some(match &self.clip {
+                None => inner,
+                Some(clip) => clip.intersect(&inner),
+            });
+        }
+        vp.shift_left += i32::from(node.scroll_offset.left);
+        vp.shift_top += i32::from(node.scroll_offset.top);
+        vp
+    }
+}
+
+// A rendered node, with where and in which order it is drawn.
+// Painting and hit-testing both rely on the same stacking of layers.
+struct Layer<'a> {
+    node: &'a RenderedNode<TUINode>,
+    vp: Viewport,
+    z_index: i32,
+    // the node or one of its ancestors is disabled
+    disabled: bool,
+}
+
+impl<'a> Layer<'a> {
+    fn is_under_mouse(&self, left: i32, top: i32) -> bool {
+        let tuinode = &self.node.payload;
+        let (node_left, node_top) = self.vp.screen_pos(tuinode);
+        self.vp.is_visible(left, top)
+            && aabb_contains(
+                node_left,
+                node_top,
+                tuinode.dim.width.into(),
+                tuinode.dim.height.into(),
+                left,
+                top,
+            )
+    }
+
+    fn is_within(&self, ancestor: &Layer) -> bool {
+        let path = &self.node.path;
+        let ancestor_path = &ancestor.node.path;
+        path == ancestor_path
+            || (path.starts_with(ancestor_path.as_str())
+                && path[ancestor_path.len()..].starts_with('/'))
+    }
+}
+
+// Layers in painting order: by z-index, then in tree order
+fn stack_layers(el: &RenderedEl<TUINode>) -> Vec<Layer<'_>> {
+    let mut layers = Vec::new();
+    collect_layers(el, &Viewport::root(), 0, false, &mut layers);
+    // stable sort: tree order is kept among layers of equal z-index
+    layers.sort_by_key(|l| l.z_index);
+    layers
+}
+
+fn collect_layers<'a>(
+    e: &'a RenderedEl<TUINode>,
+    vp: &Viewport,
+    z_index: i32,
+    disabled: bool,
+    layers: &mut Vec<Layer<'a>>,
+) {
     match e {
-        RenderedEl::Node(rel) => draw_rendered_el(stdout, rel),
-        RenderedEl::Container(c) => draw_rendered_container(stdout, c),
         RenderedEl::None => {}
+        RenderedEl::Container(cont) => {
+            for ch in cont {
+                collect_layers(ch, vp, z_index, disabled, layers);
+            }
+        }
+        RenderedEl::Node(node) => {
+            let tuinode = &node.payload;
+            let (vp, z_index) = if tuinode.portal {
+                (
+                    Viewport::root(),
+                    z_index.max(OVERLAY_Z_INDEX) + tuinode.z_index,
+                )
+            } else {
+                (*vp, z_index + tuinode.z_index)
+            };
+            let disabled = disabled || tuinode.disabled;
+
+            layers.push(Layer {
+                node,
+                vp,
+                z_index,
+                disabled,
+            });
+
+            let children_vp = vp.for_children(tuinode);
+            for ch in &node.children {
+                collect_layers(ch, &children_vp, z_index, disabled, layers);
+            }
+        }
+    }
+}
+
+// Topmost modal layer; when present, it captures the input
+fn modal_scope<'a, 'b>(layers: &'b [Layer<'a>]) -> Option<&'b Layer<'a>> {
+    layers.iter().rev().find(|l| l.node.payload.modal)
+}
+
+// Layers able to receive input, topmost first
+fn input_layers<'a, 'b>(layers: &'b [Layer<'a>]) -> impl Iterator<Item = &'b Layer<'a>> {
+    let scope = modal_scope(layers);
+    layers.iter().rev().filter(move |l| match scope {
+        None => true,
+        Some(modal) => l.is_within(modal),
+    })
+}
+
+// Topmost layer under the mouse, if it can receive input
+fn hit_test<'a, 'b>(layers: &'b [Layer<'a>], left: i32, top: i32) -> Option<&'b Layer<'a>> {
+    input_layers(layers).find(|l| l.is_under_mouse(left, top))
+}
+
+// Layers an event dispatched to target travels through, from the root down to the target
+fn propagation_path<'a, 'b>(layers: &'b [Layer<'a>], target: &Layer) -> Vec<&'b Layer<'a>> {
+    let mut path: Vec<&Layer> = layers.iter().filter(|l| target.is_within(l)).collect();
+    // the path of an ancestor is a prefix of the path of its descendants
+    path.sort_by_key(|l| l.node.path.len());
+    path
+}
+
+pub fn draw_graph(stdout: &mut RawTerminal<Stdout>, el: &RenderedEl<TUINode>) {
+    draw_graph_measured(stdout, el);
+}
+
+// Time spent and bytes written by draw_graph
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DrawMetrics {
+    // stacking of the layers and computing of their viewports
+    pub layout: Duration,
+    pub draw: Duration,
+    pub bytes: usize,
+}
+
+pub fn draw_graph_measured<W: Write>(out: &mut W, el: &RenderedEl<TUINode>) -> DrawMetrics {
+    let start = Instant::now();
+    let layers = stack_layers(el);
+    let layout = start.elapsed();
+
+    // written at once: the terminal never shows half a frame
+    let mut frame: Vec<u8> = Vec::new();
+    write!(frame, "{}{}", termion::clear::All, cursor::Hide).unwrap();
+    for layer in &layers {
+        draw_layer(&mut frame, layer);
+    }
+    out.write_all(&frame).unwrap();
+    out.flush().unwrap();
+
+    DrawMetrics {
+        layout,
+        draw: start.elapsed() - layout,
+        bytes: frame.len(),
     }
 }
 
-fn draw_rendered_el(stdout: &mut RawTerminal<Stdout>, rel: &RenderedNode<TUINode>) {
-    let b = &rel.payload;
-    let left = b.pos.left;
-    let top = b.pos.top;
+fn draw_layer<W: Write>(stdout: &mut W, layer: &Layer) {
+    let b = &layer.node.payload;
+    let vp = &layer.vp;
+    let (left, top) = vp.screen_pos(b);
+
+    if b.style.fill {
+        let blank = " ".repeat(b.dim.width.into());
+        for line in top..top + i32::from(b.dim.height) {
+            write_clipped(stdout, vp, left, line, &blank);
+        }
+    }
 
     if b.disabled {
         write!(stdout, "{}", color::Fg(color::Yellow)).unwrap();
     }
 
+    if b.style.inverse {
+        write!(stdout, "{}", style::Invert).unwrap();
+    }
+
     let text = match &b.text {
         Some(t) => t.as_str(),
         None => "",
     };
 
     if b.style.border && b.dim.height >= 3 {
-        let width = if b.dim.width >= 2 { b.dim.width - 2 } else { 0 } as usize;
-        write!(
+        let width = b.dim.width.saturating_sub(2) as usize;
+        let height = i32::from(b.dim.height);
+        write_clipped(stdout, vp, left + 1, top, &"▀".repeat(width));
+        write_clipped(stdout, vp, left + 1, top + height - 1, &"▄".repeat(width));
+        // one cell per char: truncated by chars, not bytes
+        let text: String = text.chars().take(width).collect();
+        write_clipped(
             stdout,
-            "{}{}{}{}{}{}",
-            Goto(left + 1, top),
-            "▀".repeat(width),
-            Goto(left + 1, top + b.dim.height - 1),
-            "▄".repeat(width),
-            Goto(
-                left + (b.dim.width / 2) - (text.len() as u16 / 2),
-                top + (b.dim.height / 2)
-            ),
-            if width == 0 {
-                ""
-            } else if text.len() > width {
-                text.split_at(width).0
-            } else {
-                text
-            }
-        )
-        .unwrap();
-        for line in top..top + b.dim.height {
-            write!(
-                stdout,
-                "{}█{}█",
-                Goto(left, line),
-                Goto(left + b.dim.width - 1, line),
-            )
-            .unwrap();
+            vp,
+            left + i32::from(b.dim.width / 2) - (text.chars().count() as i32 / 2),
+            top + height / 2,
+            &text,
+        );
+        for line in top..top + height {
+            write_clipped(stdout, vp, left, line, "█");
+            write_clipped(stdout, vp, left + i32::from(b.dim.width) - 1, line, "█");
         }
     } else {
-        write!(stdout, "{}{}", Goto(left, top), text).unwrap();
+        write_clipped(stdout, vp, left, top, text);
+    }
+
+    if b.style.inverse {
+        write!(stdout, "{}", style::NoInvert).unwrap();
     }
 
     if b.disabled {
         write!(stdout, "{}", color::Fg(color::Reset)).unwrap();
     }
+}
 
-    for ch in &rel.children {
-        draw_rendered(stdout, ch);
+// Writes the part of text (one cell per char) that falls within the viewport
+// and the screen, starting at left, top.
+fn write_clipped<W: Write>(stdout: &mut W, vp: &Viewport, left: i32, top: i32, text: &str) {
+    let (min_left, max_left, min_top, max_top) = match &vp.clip {
+        None => (1, i32::MAX, 1, i32::MAX),
+        Some(clip) => (
+            clip.left.max(1),
+            clip.left + clip.width,
+            clip.top.max(1),
+            clip.top + clip.height,
+        ),
+    };
+
+    if top < min_top || top >= max_top {
+        return;
     }
+
+    let skip = (min_left - left).max(0);
+    let start = left + skip;
+    let visible: String = text
+        .chars()
+        .skip(skip as usize)
+        .take((max_left - start).max(0) as usize)
+        .collect();
+
+    if !visible.is_empty() {
+        write!(stdout, "{}{}", Goto(start as u16, top as u16), visible).unwrap();
+    }
+}
+
+// Delay under which two clicks on the same node make a double click
+const DOUBLE_CLICK_DELAY: Duration = Duration::from_millis(400);
+
+// Mouse tracking kept across frames by process_events,
+// and messages returned by the handlers
+#[derive(Default)]
+pub struct InputState {
+    // last known mouse position, in screen coordinates
+    mouse: Option<(i32, i32)>,
+    // paths of the nodes under the mouse, root first
+    hovered: Vec<String>,
+    // target of the last mouse down, while the button is held
+    pressed: Option<String>,
+    last_click: Option<(String, Instant)>,
+    // path of the focusable node the last mouse down happened in
+    focused: Option<String>,
+    messages: Vec<Message>,
 }
 
-fn draw_rendered_container(stdout: &mut RawTerminal<Stdout>, cont: &[RenderedEl<TUINode>]) {
-    for ch in cont {
-        draw_rendered(stdout, ch);
+impl InputState {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn focused(&self) -> Option<&str> {
+        self.focused.as_deref()
+    }
+
+    pub fn mouse(&self) -> Option<(i32, i32)> {
+        self.mouse
+    }
+
+    // Removes the pending messages of type M, in the order they were returned;
+    // messages of other types are kept until the next process_events, which
+    // drops the messages left untaken by the previous frame
+    pub fn take_messages<M: 'static>(&mut self) -> Vec<M> {
+        let mut taken = Vec::new();
+        let mut kept = Vec::new();
+        for msg in self.messages.drain(..) {
+            match msg.downcast::<M>() {
+                Ok(m) => taken.push(*m),
+                Err(other) => kept.push(other),
+            }
+        }
+        self.messages = kept;
+        taken
     }
 }
 
+// Mouse position and modifier keys at the time of an event
+struct Pointer {
+    left: i32,
+    top: i32,
+    modifiers: Modifiers,
+}
+
+// Mouse input, as reported by the terminal
+enum MouseReport {
+    Press(MouseButton),
+    Release,
+    // move while a button is held
+    Drag,
+    Move,
+}
+
+// Reports the mouse moves with no button held, needed for hover tracking
+pub fn enable_mouse_motion<W: Write>(out: &mut W) {
+    write!(out, "\x1b[?1003h").unwrap();
+    out.flush().unwrap();
+}
+
+pub fn disable_mouse_motion<W: Write>(out: &mut W) {
+    write!(out, "\x1b[?1003l").unwrap();
+    out.flush().unwrap();
+}
+
+// Calls the handlers of app for the events; the messages they return are to be
+// taken before the next call
 pub fn process_events(
-    events_it: &mut Events<AsyncReader>,
+    events_it: &mut impl Iterator<Item = io::Result<Event>>,
     app: &Option<RenderedEl<TUINode>>,
+    input: &mut InputState,
 ) -> bool // true: quit application
 {
+    // messages of types the app does not take would pile up
+    input.messages.clear();
+
+    let layers = match app {
+        None => Vec::new(),
+        Some(n) => stack_layers(n),
+    };
+
+    // the graph changed since the last events: the mouse may hover other nodes
+    if let Some((left, top)) = input.mouse {
+        let pointer = Pointer {
+            left,
+            top,
+            modifiers: Modifiers::default(),
+        };
+        track_mouse_hover(&layers, input, &pointer);
+    }
+
     loop {
         let event = events_it.next();
         match event {
             None => return false,
-            Some(Ok(Event::Key(Key::Char(c)))) => {
-                if let 'q' = c {
+            Some(Ok(Event::Key(k))) => {
+                let consumed = track_key_pressed(&layers, input, k);
+                // q quits, unless a handler consumed it
+                if !consumed && k == Key::Char('q') {
                     return true;
                 }
             }
-            Some(Ok(Event::Mouse(me))) => match app {
-                None => {}
-                Some(n) => {
-                    if let MouseEvent::Release(left, top) = me {
-                        track_mouse_clicked(n, left, top);
-                    }
+            Some(Ok(Event::Mouse(me))) => {
+                let (report, left, top) = match me {
+                    MouseEvent::Press(button, left, top) => (MouseReport::Press(button), left, top),
+                    MouseEvent::Release(left, top) => (MouseReport::Release, left, top),
+                    MouseEvent::Hold(left, top) => (MouseReport::Drag, left, top),
+                };
+                let pointer = Pointer {
+                    left: left.into(),
+                    top: top.into(),
+                    modifiers: Modifiers::default(),
+                };
+                track_mouse(&layers, input, report, &pointer);
+            }
+            Some(Ok(Event::Unsupported(seq))) => {
+                if let Some((report, pointer)) = parse_mouse_report(&seq) {
+                    track_mouse(&layers, input, report, &pointer);
                 }
-            },
+            }
             _ => (),
         }
     }
 }
 
-fn track_mouse_clicked(el: &RenderedEl<TUINode>, left: u16, top: u16) {
-    match el {
-        RenderedEl::None => {}
-        RenderedEl::Node(node) => {
-            let tuinode = &node.payload;
+// termion leaves unsupported the xterm SGR reports (ESC [ < Cb ; Cx ; Cy M|m)
+// of moves with no or another button than the left one held, and of
+// events with modifier keys
+fn parse_mouse_report(seq: &[u8]) -> Option<(MouseReport, Pointer)> {
+    let seq = std::str::from_utf8(seq).ok()?.strip_prefix("\x1b[<")?;
+    let (params, pressed) = match seq.strip_suffix('M') {
+        Some(params) => (params, true),
+        None => (seq.strip_suffix('m')?, false),
+    };
+    let nums = params
+        .split(';')
+        .map(|n| n.parse::<i32>().ok())
+        .collect::<Option<Vec<i32>>>()?;
+
+    let (cb, left, top) = match nums.as_slice() {
+        [cb, left, top] => (*cb, *left, *top),
+        _ => return None,
+    };
+
+    let modifiers = Modifiers {
+        shift: cb & 4 != 0,
+        alt: cb & 8 != 0,
+        ctrl: cb & 16 != 0,
+    };
+    let button = match (cb & 64 != 0, cb & 3) {
+        (false, 0) => Some(MouseButton::Left),
+        (false, 1) => Some(MouseButton::Middle),
+        (false, 2) => Some(MouseButton::Right),
+        (true, 0) => Some(MouseButton::WheelUp),
+        (true, 1) => Some(MouseButton::WheelDown),
+        _ => None,
+    };
+
+    let report = match (pressed, cb & 32 != 0, button) {
+        (false, _, _) => MouseReport::Release,
+        (true, true, None) => MouseReport::Move,
+        (true, true, Some(_)) => MouseReport::Drag,
+        (true, false, Some(button)) => MouseReport::Press(button),
+        (true, false, None) => return None,
+    };
+
+    Some((
+        report,
+        Pointer {
+            left,
+            top,
+            modifiers,
+        },
+    ))
+}
+
+fn track_mouse(layers: &[Layer], input: &mut InputState, report: MouseReport, pointer: &Pointer) {
+    match report {
+        MouseReport::Press(MouseButton::WheelUp) => {
+            track_mouse_wheel(layers, input, WheelDirection::Up, pointer)
+        }
+        MouseReport::Press(MouseButton::WheelDown) => {
+            track_mouse_wheel(layers, input, WheelDirection::Down, pointer)
+        }
+        MouseReport::Press(button) => track_mouse_down(layers, input, button, pointer),
+        MouseReport::Release => track_mouse_up(layers, input, pointer),
+        MouseReport::Drag => track_mouse_move(layers, input, true, pointer),
+        MouseReport::Move => track_mouse_move(layers, input, false, pointer),
+    }
+}
+
+fn track_mouse_down(
+    layers: &[Layer],
+    input: &mut InputState,
+    button: MouseButton,
+    pointer: &Pointer,
+) {
+    track_mouse_hover(layers, input, pointer);
+    input.pressed = None;
+
+    if let Some(target) = hit_test(layers, pointer.left, pointer.top).filter(|t| !t.disabled) {
+        let path = propagation_path(layers, target);
+        input.pressed = Some(target.node.path.clone());
+        input.focused = path
+            .iter()
+            .rev()
+            .find(|l| l.node.payload.focusable)
+            .map(|l| l.node.path.clone());
+        dispatch(
+            &path,
+            &mut UIEvent::new(target, MouseAction::Down(button), pointer),
+            |h| h.on_mouse_down.as_ref(),
+            &mut input.messages,
+        );
+    }
+}
+
+fn track_mouse_up(layers: &[Layer], input: &mut InputState, pointer: &Pointer) {
+    track_mouse_hover(layers, input, pointer);
+    let pressed = input.pressed.take();
+
+    let target = match hit_test(layers, pointer.left, pointer.top) {
+        Some(target) => target,
+        None => {
+            track_click_outside(layers, input, pointer);
+            return;
+        }
+    };
+
+    if target.disabled {
+        return;
+    }
+
+    let path = propagation_path(layers, target);
+    dispatch(
+        &path,
+        &mut UIEvent::new(target, MouseAction::Up, pointer),
+        |h| h.on_mouse_up.as_ref(),
+        &mut input.messages,
+    );
+
+    // a click is a mouse down and up on the same node
+    match pressed {
+        Some(p) if p != target.node.path => return,
+        _ => {}
+    }
+
+    dispatch_click(
+        &path,
+        &mut UIEvent::new(target, MouseAction::Click, pointer),
+        &mut input.messages,
+    );
+
+    let now = Instant::now();
+    match input.last_click.take() {
+        Some((p, at)) if p == target.node.path && now.duration_since(at) < DOUBLE_CLICK_DELAY => {
+            dispatch(
+                &path,
+                &mut UIEvent::new(target, MouseAction::DoubleClick, pointer),
+                |h| h.on_double_click.as_ref(),
+                &mut input.messages,
+            );
+        }
+        _ => input.last_click = Some((target.node.path.clone(), now)),
+    }
+}
+
+fn track_click_outside(layers: &[Layer], input: &mut InputState, pointer: &Pointer) {
+    if let Some(modal) = modal_scope(layers) {
+        if let Some(h) = &modal.node.payload.event_handlers.on_click_outside {
+            call_handler(
+                h,
+                modal,
+                &mut UIEvent::new(modal, MouseAction::Click, pointer),
+                &mut input.messages,
+            );
+        }
+    }
+}
+
+fn track_mouse_move(layers: &[Layer], input: &mut InputState, dragging: bool, pointer: &Pointer) {
+    track_mouse_hover(layers, input, pointer);
+
+    // while dragging, the node the drag started on keeps receiving the moves
+    let pressed = match &input.pressed {
+        Some(p) if dragging => layers.iter().find(|l| &l.node.path == p),
+        _ => None,
+    };
+
+    let target = match pressed.or_else(|| hit_test(layers, pointer.left, pointer.top)) {
+        Some(t) if !t.disabled => t,
+        _ => return,
+    };
+
+    let action = if dragging {
+        MouseAction::Drag
+    } else {
+        MouseAction::Move
+    };
+    dispatch(
+        &propagation_path(layers, target),
+        &mut UIEvent::new(target, action, pointer),
+        |h| h.on_mouse_move.as_ref(),
+        &mut input.messages,
+    );
+}
+
+// Calls the leave handlers of the nodes no longer under the mouse, deepest
+// first, then the enter handlers of the nodes newly under it, root first
+fn track_mouse_hover(layers: &[Layer], input: &mut InputState, pointer: &Pointer) {
+    input.mouse = Some((pointer.left, pointer.top));
+
+    let hovered: Vec<&Layer> = match hit_test(layers, pointer.left, pointer.top) {
+        None => Vec::new(),
+        Some(target) => propagation_path(layers, target),
+    };
+
+    for path in input.hovered.iter().rev() {
+        if hovered.iter().any(|l| &l.node.path == path) {
+            continue;
+        }
+        // nodes removed from the graph are not notified
+        if let Some(layer) = layers.iter().find(|l| &l.node.path == path) {
+            call_hover_handler(layer, MouseAction::Leave, pointer, &mut input.messages);
+        }
+    }
+
+    for layer in &hovered {
+        if !input.hovered.contains(&layer.node.path) {
+            call_hover_handler(layer, MouseAction::Enter, pointer, &mut input.messages);
+        }
+    }
+
+    input.hovered = hovered.iter().map(|l| l.node.path.clone()).collect();
+}
+
+fn call_hover_handler(
+    layer: &Layer,
+    action: MouseAction,
+    pointer: &Pointer,
+    messages: &mut Vec<Message>,
+) {
+    if layer.disabled {
+        return;
+    }
 
-            if tuinode.disabled {
+    let handlers = &layer.node.payload.event_handlers;
+    let handler = match action {
+        MouseAction::Enter => &handlers.on_mouse_enter,
+        _ => &handlers.on_mouse_leave,
+    };
+
+    if let Some(h) = handler {
+        call_handler(
+            h,
+            layer,
+            &mut UIEvent::new(layer, action, pointer),
+            messages,
+        );
+    }
+}
+
+// Capture handlers are called from the root down to the target,
+// then click handlers from the target up to the root
+fn dispatch_click(path: &[&Layer], event: &mut UIEvent, messages: &mut Vec<Message>) {
+    for (i, layer) in path.iter().enumerate() {
+        if let Some(h) = &layer.node.payload.event_handlers.on_click_capture {
+            event.phase = if i + 1 == path.len() {
+                EventPhase::Target
+            } else {
+                EventPhase::Capture
+            };
+            if call_handler(h, layer, event, messages) {
                 return;
             }
+        }
+    }
 
-            if aabb_contains(
-                tuinode.pos.left,
-                tuinode.pos.top,
-                tuinode.dim.width,
-                tuinode.dim.height,
-                left,
-                top,
-            ) {
-                if let Some(c) = &node.payload.event_handlers.on_click {
-                    {
-                        let my_box_arc = c.clone();
-                        let my_box = my_box_arc.lock().unwrap();
-                        (*my_box)();
-                        return;
-                    }
-                }
-            }
+    dispatch(path, event, |h| h.on_click.as_ref(), messages);
+}
 
-            for c in &node.children {
-                track_mouse_clicked(c, left, top)
+// Calls the handlers from the target up to the root, until the propagation is stopped
+fn dispatch<F>(path: &[&Layer], event: &mut UIEvent, handler: F, messages: &mut Vec<Message>)
+where
+    F: Fn(&EventHandlers) -> Option<&MouseHandler>,
+{
+    for (i, layer) in path.iter().enumerate().rev() {
+        if let Some(h) = handler(&layer.node.payload.event_handlers) {
+            event.phase = if i + 1 == path.len() {
+                EventPhase::Target
+            } else {
+                EventPhase::Bubble
+            };
+            if call_handler(h, layer, event, messages) {
+                return;
             }
         }
-        RenderedEl::Container(container) => {
-            for c in container {
-                track_mouse_clicked(c, left, top)
+    }
+}
+
+// true when the handler stopped the propagation
+fn call_handler(
+    h: &MouseHandler,
+    layer: &Layer,
+    event: &mut UIEvent,
+    messages: &mut Vec<Message>,
+) -> bool {
+    event.current_target = layer.node.path.clone();
+    if let Some(msg) = h(event) {
+        messages.push(msg);
+    }
+    event.is_propagation_stopped()
+}
+
+fn track_mouse_wheel(
+    layers: &[Layer],
+    input: &mut InputState,
+    direction: WheelDirection,
+    pointer: &Pointer,
+) {
+    if let Some(target) = hit_test(layers, pointer.left, pointer.top).filter(|t| !t.disabled) {
+        dispatch(
+            &propagation_path(layers, target),
+            &mut UIEvent::new(target, MouseAction::Wheel(direction), pointer),
+            |h| h.on_wheel.as_ref(),
+            &mut input.messages,
+        );
+    }
+}
+
+// Key handlers are offered the key from the focused node up to the root,
+// then on the other nodes, topmost first, until one stops its propagation.
+// Unfocused focusable nodes are skipped. Returns true if the key was consumed.
+fn track_key_pressed(layers: &[Layer], input: &mut InputState, key: Key) -> bool {
+    let mut event = KeyEvent {
+        key,
+        current_target: String::new(),
+        propagation_stopped: false,
+    };
+
+    let focused = input
+        .focused
+        .as_ref()
+        .and_then(|f| input_layers(layers).find(|l| &l.node.path == f));
+    let focus_path = match focused {
+        Some(f) if !f.disabled => propagation_path(layers, f),
+        _ => Vec::new(),
+    };
+
+    let others = input_layers(layers).filter(|l| {
+        !l.disabled
+            && !l.node.payload.focusable
+            && !focus_path.iter().any(|f| f.node.path == l.node.path)
+    });
+
+    for layer in focus_path.iter().rev().copied().chain(others) {
+        if let Some(h) = &layer.node.payload.event_handlers.on_key {
+            event.current_target = layer.node.path.clone();
+            if let Some(msg) = h(&mut event) {
+                input.messages.push(msg);
+            }
+            if event.is_propagation_stopped() {
+                return true;
             }
         }
-    };
+    }
+
+    false
 }
 
 fn aabb_contains(
-    left: u16,
-    top: u16,
-    width: u16,
-    height: u16,
-    point_left: u16,
-    point_top: u16,
+    left: i32,
+    top: i32,
+    width: i32,
+    height: i32,
+    point_left: i32,
+    point_top: i32,
 ) -> bool {
-    left <= point_left
-        && left + width >= point_left
-        && top <= point_top
-        && top + height >= point_top
+    // right and bottom edges are excluded: a 1x1 box only contains its own cell
+    left <= point_left && point_left < left + width && top <= point_top && point_top < top + height
 }
 
 pub struct VSync {
     last: Option<Instant>,
     every: Duration,
+    clock: animation::FrameClock,
 }
 
 impl VSync {
     pub fn new(every: Duration) -> VSync {
-        VSync { last: None, every }
+        VSync {
+            last: None,
+            every,
+            clock: animation::FrameClock::new(),
+        }
+    }
+
+    // Clock ticked at each frame, for the animations
+    pub fn clock(&self) -> animation::FrameClock {
+        self.clock.clone()
+    }
+
+    // Target interval between two frames
+    pub fn every(&self) -> Duration {
+        self.every
+    }
+
+    // Earliest time of the next frame
+    pub fn next_frame(&self) -> Instant {
+        match self.last {
+            None => Instant::now(),
+            Some(t) => t + self.every,
+        }
     }
 
     pub fn wait(&mut self) {
@@ -285,6 +1259,250 @@ impl VSync {
             sleep(to_wait);
         }
 
-        self.last = Some(Instant::now());
+        let now = Instant::now();
+        self.last = Some(now);
+        self.clock.tick(now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use termion::event::MouseEvent;
+
+    fn boxed(name: &str, left: u16, top: u16, width: u16, height: u16) -> TUINode {
+        TUINode::new(left, top)
+            .set_dimension(width, height)
+            .set_text(Some(name.to_string()))
+    }
+
+    fn node(payload: TUINode) -> El<TUINode> {
+        El::Node(Node::new(payload))
+    }
+
+    fn graph(el: El<TUINode>) -> RenderedEl<TUINode> {
+        render_app_to_graph(&el, new_state_store())
+    }
+
+    // Text of the node hit
+    fn hit(el: El<TUINode>, left: i32, top: i32) -> Option<String> {
+        let graph = graph(el);
+        let layers = stack_layers(&graph);
+        hit_test(&layers, left, top).and_then(|l| l.node.payload.text.clone())
+    }
+
+    #[test]
+    fn aabb_excludes_right_and_bottom_edges() {
+        assert!(aabb_contains(2, 3, 4, 2, 2, 3));
+        assert!(aabb_contains(2, 3, 4, 2, 5, 4));
+        assert!(!aabb_contains(2, 3, 4, 2, 6, 3));
+        assert!(!aabb_contains(2, 3, 4, 2, 2, 5));
+        assert!(!aabb_contains(2, 3, 4, 2, 1, 3));
+        assert!(!aabb_contains(2, 3, 4, 2, 2, 2));
+    }
+
+    #[test]
+    fn aabb_of_small_boxes() {
+        assert!(aabb_contains(5, 5, 1, 1, 5, 5));
+        for (left, top) in [(4, 5), (6, 5), (5, 4), (5, 6)] {
+            assert!(!aabb_contains(5, 5, 1, 1, left, top));
+        }
+        assert!(!aabb_contains(5, 5, 0, 0, 5, 5));
+        assert!(!aabb_contains(5, 5, 0, 3, 5, 5));
+        assert!(!aabb_contains(5, 5, 3, 0, 5, 5));
+    }
+
+    #[test]
+    fn hit_test_uses_exact_bounds() {
+        let a = || node(boxed("a", 2, 2, 3, 2));
+        assert_eq!(hit(a(), 2, 2), Some("a".to_string()));
+        assert_eq!(hit(a(), 4, 3), Some("a".to_string()));
+        assert_eq!(hit(a(), 5, 3), None);
+        assert_eq!(hit(a(), 4, 4), None);
+        assert_eq!(hit(node(boxed("a", 2, 2, 0, 0)), 2, 2), None);
+    }
+
+    #[test]
+    fn topmost_wins() {
+        // later in the tree, on equal z-index
+        let el = El::Container(vec![
+            node(boxed("a", 1, 1, 10, 10)),
+            node(boxed("b", 1, 1, 10, 10)),
+        ]);
+        assert_eq!(hit(el, 5, 5), Some("b".to_string()));
+
+        // higher z-index, whatever the tree order
+        let el = El::Container(vec![
+            node(boxed("a", 1, 1, 10, 10).set_z_index(1)),
+            node(boxed("b", 1, 1, 10, 10)),
+        ]);
+        assert_eq!(hit(el, 5, 5), Some("a".to_string()));
+
+        // children inherit the z-index of their parent
+        let el = El::Container(vec![
+            El::Node(
+                Node::new(boxed("a", 1, 1, 10, 10).set_z_index(2))
+                    .add_child(node(boxed("a child", 1, 1, 2, 2))),
+            ),
+            node(boxed("b", 1, 1, 10, 10).set_z_index(1)),
+        ]);
+        assert_eq!(hit(el, 1, 1), Some("a child".to_string()));
+    }
+
+    #[test]
+    fn portals_are_above_and_out_of_clipping() {
+        let el = El::Container(vec![
+            El::Node(
+                Node::new(boxed("clip", 1, 1, 5, 5).set_clip(true))
+                    .add_child(node(boxed("clipped", 1, 1, 20, 20)))
+                    .add_child(node(boxed("portal", 8, 8, 2, 2).set_portal(true))),
+            ),
+            node(boxed("above", 1, 1, 20, 20).set_z_index(10)),
+        ]);
+        let graph = graph(el);
+        let layers = stack_layers(&graph);
+        let text =
+            |left, top| hit_test(&layers, left, top).and_then(|l| l.node.payload.text.clone());
+        assert_eq!(text(8, 8), Some("portal".to_string()));
+        assert_eq!(text(3, 3), Some("above".to_string()));
+    }
+
+    #[test]
+    fn modals_capture_the_input() {
+        let el = El::Container(vec![
+            node(boxed("back", 1, 1, 20, 20)),
+            El::Node(
+                Node::new(boxed("modal", 5, 5, 5, 5).set_modal(true))
+                    .add_child(node(boxed("inner", 6, 6, 2, 2))),
+            ),
+            // above the modal, but out of it
+            node(boxed("front", 6, 6, 2, 2)),
+        ]);
+        let graph = graph(el);
+        let layers = stack_layers(&graph);
+        let text =
+            |left, top| hit_test(&layers, left, top).and_then(|l| l.node.payload.text.clone());
+        assert_eq!(text(6, 6), Some("inner".to_string()));
+        assert_eq!(text(8, 8), Some("modal".to_string()));
+        assert_eq!(text(2, 2), None);
+    }
+
+    type Log = Rc<RefCell<Vec<String>>>;
+
+    fn logger(log: &Log, name: &'static str, stop: Option<&'static str>) -> MouseHandler {
+        let log = Rc::clone(log);
+        Rc::new(move |event: &mut UIEvent| {
+            log.borrow_mut().push(format!("{} {:?}", name, event.phase));
+            if stop == Some(name) {
+                event.stop_propagation();
+            }
+            None
+        })
+    }
+
+    // Clicks the innermost of three nested nodes, logging the handlers called
+    fn click_nested(stop: Option<&'static str>) -> Vec<String> {
+        let log: Log = Rc::new(RefCell::new(Vec::new()));
+        let level = |name: &'static str, capture: &'static str, offset: u16| {
+            boxed(name, 1 + offset, 1 + offset, 10 - offset, 10 - offset)
+                .set_on_click(Some(logger(&log, name, stop)))
+                .set_on_click_capture(Some(logger(&log, capture, stop)))
+        };
+        let el = El::Node(Node::new(level("a", "capture a", 0)).add_child(El::Node(
+            Node::new(level("b", "capture b", 1)).add_child(node(level("c", "capture c", 2))),
+        )));
+
+        let graph = Some(graph(el));
+        let mut input = InputState::new();
+        let events = vec![
+            Ok(Event::Mouse(MouseEvent::Press(MouseButton::Left, 5, 5))),
+            Ok(Event::Mouse(MouseEvent::Release(5, 5))),
+        ];
+        process_events(&mut events.into_iter(), &graph, &mut input);
+        let log = log.borrow().clone();
+        log
+    }
+
+    #[test]
+    fn click_goes_through_capture_target_and_bubble() {
+        assert_eq!(
+            click_nested(None),
+            [
+                "capture a Capture",
+                "capture b Capture",
+                "capture c Target",
+                "c Target",
+                "b Bubble",
+                "a Bubble",
+            ]
+        );
+    }
+
+    #[test]
+    fn stop_propagation_at_each_phase() {
+        assert_eq!(click_nested(Some("capture a")), ["capture a Capture"]);
+        assert_eq!(
+            click_nested(Some("capture c")),
+            ["capture a Capture", "capture b Capture", "capture c Target"]
+        );
+        assert_eq!(
+            click_nested(Some("c")),
+            [
+                "capture a Capture",
+                "capture b Capture",
+                "capture c Target",
+                "c Target"
+            ]
+        );
+        assert_eq!(
+            click_nested(Some("b")),
+            [
+                "capture a Capture",
+                "capture b Capture",
+                "capture c Target",
+                "c Target",
+                "b Bubble"
+            ]
+        );
+    }
+
+    #[test]
+    fn untaken_messages_are_dropped_by_the_next_frame() {
+        let el = node(
+            boxed("a", 1, 1, 5, 5)
+                .set_on_click(Some(Rc::new(|_: &mut UIEvent| message(1u8))))
+                .set_on_mouse_down(Some(Rc::new(|_: &mut UIEvent| message("down")))),
+        );
+        let graph = Some(graph(el));
+        let mut input = InputState::new();
+        let click = || {
+            vec![
+                Ok(Event::Mouse(MouseEvent::Press(MouseButton::Left, 2, 2))),
+                Ok(Event::Mouse(MouseEvent::Release(2, 2))),
+            ]
+            .into_iter()
+        };
+
+        process_events(&mut click(), &graph, &mut input);
+        assert_eq!(input.take_messages::<u8>(), [1]);
+        assert_eq!(input.messages.len(), 1);
+        process_events(&mut click(), &graph, &mut input);
+        assert_eq!(input.messages.len(), 2);
+        assert_eq!(input.take_messages::<&str>(), ["down"]);
+    }
+
+    #[test]
+    fn disabled_nodes_receive_no_click() {
+        let log: Log = Rc::new(RefCell::new(Vec::new()));
+        let mut target = boxed("a", 1, 1, 5, 5).set_on_click(Some(logger(&log, "a", None)));
+        target.disabled = true;
+        let graph = Some(graph(node(target)));
+        let events = vec![
+            Ok(Event::Mouse(MouseEvent::Press(MouseButton::Left, 2, 2))),
+            Ok(Event::Mouse(MouseEvent::Release(2, 2))),
+        ];
+        process_events(&mut events.into_iter(), &graph, &mut InputState::new());
+        assert!(log.borrow().is_empty());
     }
 }
@@ -0,0 +1,73 @@
+use std::rc::Rc;
+
+use termion::event::Key;
+
+use crate::engine::*;
+use crate::frontend::tui::*;
+
+// Moves content to the overlay layer: it is drawn above the regular content,
+// whatever its place in the tree, and is not clipped nor scrolled by its ancestors.
+pub fn overlay(content: El<TUINode>) -> El<TUINode> {
+    El::Node(Node::new(TUINode::new(1, 1).set_dimension(0, 0).set_portal(true)).add_child(content))
+}
+
+pub type DismissHandler = Rc<dyn Fn() -> Option<Message>>;
+
+pub struct ModalProps {
+    pub pos: Position,
+    pub dim: Dimension,
+    pub title: String,
+    // called on click outside of the dialog, or on Esc
+    pub on_dismiss: Option<DismissHandler>,
+}
+
+impl Default for ModalProps {
+    fn default() -> Self {
+        ModalProps {
+            pos: Position {
+                ..Default::default()
+            },
+            dim: Dimension {
+                ..Default::default()
+            },
+            title: "".to_string(),
+            on_dismiss: None,
+        }
+    }
+}
+
+// Dialog box in the overlay layer, capturing the input while it is rendered
+pub fn modal(props: ModalProps, content: El<TUINode>) -> El<TUINode> {
+    let on_click_outside = props.on_dismiss.clone().map(handle_on_click_outside);
+    let on_key = props.on_dismiss.map(handle_on_key);
+
+    overlay(El::Node(
+        Node::new(
+            TUINode::new(props.pos.left, props.pos.top)
+                .set_dimension(props.dim.width, props.dim.height)
+                .set_border(true)
+                .set_fill(true)
+                .set_modal(true)
+                .set_on_click_outside(on_click_outside)
+                .set_on_key(on_key),
+        )
+        .add_child(El::Node(Node::new(
+            TUINode::new(props.pos.left + 2, props.pos.top + 1).set_text(Some(props.title)),
+        )))
+        .add_child(content),
+    ))
+}
+
+fn handle_on_click_outside(on_dismiss: DismissHandler) -> MouseHandler {
+    Rc::new(move |_: &mut UIEvent| on_dismiss())
+}
+
+fn handle_on_key(on_dismiss: DismissHandler) -> KeyHandler {
+    Rc::new(move |event: &mut KeyEvent| {
+        if event.key != Key::Esc {
+            return None;
+        }
+        event.stop_propagation();
+        on_dismiss()
+    })
+}
@@ -0,0 +1,389 @@
+use std::cell::RefCell;
+use std::io::{self, Write};
+use std::os::unix::io::RawFd;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+use std::time::{Duration, Instant};
+
+use termion::event::Event;
+use termion::input::TermRead;
+
+use crate::engine::*;
+use crate::frontend::tui::animation::FrameClock;
+use crate::frontend::tui::VSync;
+
+// Write end of the wake pipe of the runtime, for the signal handler
+static WAKE_FD: AtomicI32 = AtomicI32::new(-1);
+static RESIZED: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn on_resize(_: libc::c_int) {
+    RESIZED.store(true, Ordering::SeqCst);
+    wake(WAKE_FD.load(Ordering::SeqCst));
+}
+
+// Async-signal-safe: a full pipe already wakes the runtime up
+fn wake(fd: RawFd) {
+    if fd >= 0 {
+        unsafe {
+            libc::write(fd, b"!".as_ptr() as *const libc::c_void, 1);
+        }
+    }
+}
+
+// Leaves raw mode and the mouse reporting, and shows the cursor: the panic
+// message is then readable, and the shell usable
+fn restore_terminal() {
+    unsafe {
+        let mut t: libc::termios = std::mem::zeroed();
+        if libc::tcgetattr(libc::STDOUT_FILENO, &mut t) == 0 {
+            t.c_iflag |= libc::ICRNL | libc::IXON;
+            t.c_oflag |= libc::OPOST | libc::ONLCR;
+            t.c_lflag |= libc::ECHO | libc::ICANON | libc::ISIG | libc::IEXTEN;
+            libc::tcsetattr(libc::STDOUT_FILENO, libc::TCSANOW, &t);
+        }
+    }
+    let mut out = io::stdout();
+    let _ = write!(
+        out,
+        "\x1b[?1003l\x1b[?1002l\x1b[?1000l\x1b[?1015l\x1b[?1006l\x1b[0m\x1b[?25h\r\n"
+    );
+    let _ = out.flush();
+}
+
+fn check(ret: libc::c_int) -> io::Result<libc::c_int> {
+    match ret {
+        -1 => Err(io::Error::last_os_error()),
+        r => Ok(r),
+    }
+}
+
+// Waits for something to happen instead of polling at a fixed rate: input on
+// stdin, a due timer, a state update posted by another thread, a terminal
+// resize, or the next frame of a running animation. The VSync it owns caps the
+// frame rate. While it exists, a panic restores the terminal before printing.
+pub struct Runtime {
+    state_store: Rc<RefCell<StateStore>>,
+    vsync: VSync,
+    wake_read: RawFd,
+    wake_write: RawFd,
+    // the app was never rendered, or the terminal was resized since
+    redraw: bool,
+    // start of an escape sequence cut by the end of the last read
+    input: Vec<u8>,
+    _panic_cleanup: PanicCleanup,
+}
+
+impl Runtime {
+    // Only one runtime may exist at a time: it handles SIGWINCH
+    pub fn new(state_store: Rc<RefCell<StateStore>>, max_fps_every: Duration) -> io::Result<Self> {
+        let mut fds = [0 as RawFd; 2];
+        unsafe {
+            check(libc::pipe(fds.as_mut_ptr()))?;
+            for fd in &fds {
+                check(libc::fcntl(*fd, libc::F_SETFL, libc::O_NONBLOCK))?;
+                check(libc::fcntl(*fd, libc::F_SETFD, libc::FD_CLOEXEC))?;
+            }
+        }
+        let (wake_read, wake_write) = (fds[0], fds[1]);
+        WAKE_FD.store(wake_write, Ordering::SeqCst);
+
+        unsafe {
+            let mut action: libc::sigaction = std::mem::zeroed();
+            action.sa_sigaction = on_resize as extern "C" fn(libc::c_int) as libc::sighandler_t;
+            action.sa_flags = libc::SA_RESTART;
+            check(libc::sigemptyset(&mut action.sa_mask))?;
+            check(libc::sigaction(
+                libc::SIGWINCH,
+                &action,
+                std::ptr::null_mut(),
+            ))?;
+        }
+
+        state_store
+            .borrow()
+            .scheduler()
+            .borrow()
+            .set_waker(move || wake(wake_write));
+        // panics of the UI thread not caught by an error boundary end the app
+        let panic_cleanup = on_uncaught_panic(restore_terminal);
+
+        Ok(Runtime {
+            state_store,
+            vsync: VSync::new(max_fps_every),
+            wake_read,
+            wake_write,
+            redraw: true,
+            input: Vec::new(),
+            _panic_cleanup: panic_cleanup,
+        })
+    }
+
+    pub fn clock(&self) -> FrameClock {
+        self.vsync.clock()
+    }
+
+    // Target interval between two frames
+    pub fn frame_interval(&self) -> Duration {
+        self.vsync.every()
+    }
+
+    fn render_pending(&self) -> bool {
+        self.redraw
+            || self.state_store.borrow().has_changed()
+            || self.vsync.clock().is_frame_requested()
+    }
+
+    // When wait is to return at the latest: at the next frame if a render is
+    // pending, else when the next timer is due. None: only input wakes it up.
+    fn deadline(&self) -> Option<Instant> {
+        if self.render_pending() {
+            Some(self.vsync.next_frame())
+        } else {
+            self.state_store.borrow().scheduler().borrow().next_due()
+        }
+    }
+
+    // Blocks until something happens, and returns the input events read.
+    // Returns without events when a render is due: the frame rate is capped,
+    // and the scheduled work is left to run_scheduled.
+    pub fn wait(&mut self) -> io::Result<Vec<io::Result<Event>>> {
+        let timeout = poll_timeout(self.deadline(), Instant::now());
+
+        let mut fds = [
+            libc::pollfd {
+                fd: libc::STDIN_FILENO,
+                events: libc::POLLIN,
+                revents: 0,
+            },
+            libc::pollfd {
+                fd: self.wake_read,
+                events: libc::POLLIN,
+                revents: 0,
+            },
+        ];
+        let ready = unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, timeout) };
+        if ready < 0 {
+            let err = io::Error::last_os_error();
+            // interrupted by a signal: its handler woke us up
+            if err.kind() != io::ErrorKind::Interrupted {
+                return Err(err);
+            }
+        }
+
+        if fds[1].revents & libc::POLLIN != 0 {
+            self.drain_wake_pipe();
+        }
+        if RESIZED.swap(false, Ordering::SeqCst) {
+            self.redraw = true;
+        }
+
+        if fds[0].revents & libc::POLLIN != 0 {
+            return self.read_events();
+        }
+        Ok(Vec::new())
+    }
+
+    fn drain_wake_pipe(&self) {
+        let mut buf = [0u8; 64];
+        loop {
+            let n = unsafe {
+                libc::read(
+                    self.wake_read,
+                    buf.as_mut_ptr() as *mut libc::c_void,
+                    buf.len(),
+                )
+            };
+            if n <= 0 {
+                return;
+            }
+        }
+    }
+
+    // Reads the bytes available on stdin, and parses them to events. An escape
+    // sequence cut by the end of the read is parsed with the next bytes.
+    fn read_events(&mut self) -> io::Result<Vec<io::Result<Event>>> {
+        let mut buf = [0u8; 4096];
+        let n = unsafe {
+            libc::read(
+                libc::STDIN_FILENO,
+                buf.as_mut_ptr() as *mut libc::c_void,
+                buf.len(),
+            )
+        };
+        match n {
+            -1 => {
+                let err = io::Error::last_os_error();
+                match err.kind() {
+                    io::ErrorKind::Interrupted | io::ErrorKind::WouldBlock => Ok(Vec::new()),
+                    _ => Err(err),
+                }
+            }
+            // stdin would stay readable forever
+            0 => Err(io::Error::new(io::ErrorKind::UnexpectedEof, "stdin closed")),
+            n => {
+                self.input.extend_from_slice(&buf[..n as usize]);
+                let cut = self.input.split_off(complete_len(&self.input));
+                let events = (&self.input[..]).events().collect();
+                self.input = cut;
+                Ok(events)
+            }
+        }
+    }
+
+    // Whether the app is to be rendered now: after a resize, a state change, or
+    // for the next frame of an animation. Waits for the frame rate cap.
+    pub fn needs_render(&mut self) -> bool {
+        let clock = self.vsync.clock();
+        if !self.render_pending() {
+            return false;
+        }
+        clock.take_frame_request();
+        self.redraw = false;
+        self.vsync.wait();
+        true
+    }
+}
+
+impl Drop for Runtime {
+    fn drop(&mut self) {
+        WAKE_FD.store(-1, Ordering::SeqCst);
+        self.state_store
+            .borrow()
+            .scheduler()
+            .borrow()
+            .set_waker(|| {});
+        unsafe {
+            libc::signal(libc::SIGWINCH, libc::SIG_DFL);
+            libc::close(self.wake_read);
+            libc::close(self.wake_write);
+        }
+    }
+}
+
+// Milliseconds poll waits for deadline, -1 for ever
+fn poll_timeout(deadline: Option<Instant>, now: Instant) -> libc::c_int {
+    match deadline {
+        None => -1,
+        // rounded up, not to wake up just before the deadline
+        Some(d) => d
+            .saturating_duration_since(now)
+            .as_nanos()
+            .div_ceil(1_000_000)
+            .min(i32::MAX as u128) as libc::c_int,
+    }
+}
+
+// Length of bytes without the escape sequence or UTF-8 character cut at their
+// end. A lone ESC at the end is the Esc key, not the start of a sequence.
+fn complete_len(bytes: &[u8]) -> usize {
+    if let Some(esc) = bytes.iter().rposition(|b| *b == 0x1b) {
+        match &bytes[esc..] {
+            [_, b'['] | [_, b'O'] => return esc,
+            // X10 mouse reports end with 3 raw bytes
+            [_, b'[', b'M', rest @ ..] if rest.len() < 3 => return esc,
+            [_, b'[', b'M', _, _, _] => return bytes.len(),
+            // parameter and intermediate bytes, without the final one yet
+            [_, b'[', rest @ ..] if rest.iter().all(|b| (0x20..0x40).contains(b)) => return esc,
+            _ => {}
+        }
+    }
+
+    // the first byte of a UTF-8 character tells how many follow it
+    let tail = bytes.len().saturating_sub(3);
+    match bytes[tail..].iter().rposition(|b| b & 0xc0 == 0xc0) {
+        Some(i) => {
+            let start = tail + i;
+            let len = match bytes[start] {
+                b if b >= 0xf0 => 4,
+                b if b >= 0xe0 => 3,
+                _ => 2,
+            };
+            if bytes.len() - start < len {
+                start
+            } else {
+                bytes.len()
+            }
+        }
+        None => bytes.len(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timeouts_are_rounded_up_to_the_millisecond() {
+        let now = Instant::now();
+        assert_eq!(poll_timeout(None, now), -1);
+        assert_eq!(poll_timeout(Some(now), now), 0);
+        assert_eq!(poll_timeout(Some(now - Duration::from_secs(1)), now), 0);
+        assert_eq!(
+            poll_timeout(Some(now + Duration::from_micros(1500)), now),
+            2
+        );
+        assert_eq!(poll_timeout(Some(now + Duration::from_millis(40)), now), 40);
+        assert_eq!(
+            poll_timeout(Some(now + Duration::from_secs(u64::from(u32::MAX))), now),
+            i32::MAX
+        );
+    }
+
+    #[test]
+    fn cut_sequences_are_left_for_the_next_read() {
+        // cut
+        assert_eq!(complete_len(b"ab\x1b["), 2);
+        assert_eq!(complete_len(b"\x1bO"), 0);
+        assert_eq!(complete_len(b"a\x1b[<0;12"), 1);
+        assert_eq!(complete_len(b"\x1b[M !"), 0);
+        assert_eq!(
+            complete_len("a\u{e9}".as_bytes().split_last().unwrap().1),
+            1
+        );
+        assert_eq!(complete_len(&"\u{1f600}".as_bytes()[..3]), 0);
+        // complete
+        assert_eq!(complete_len(b"a\x1b"), 2);
+        assert_eq!(complete_len(b"\x1b[A"), 3);
+        assert_eq!(complete_len(b"\x1b[<0;12;4M"), 10);
+        assert_eq!(complete_len(b"\x1b[M \xff\xff"), 6);
+        assert_eq!(complete_len(b"\x1bOPa"), 4);
+        assert_eq!(complete_len("\u{e9}a".as_bytes()), 3);
+        assert_eq!(complete_len("\u{1f600}".as_bytes()), 4);
+    }
+
+    #[test]
+    fn renders_are_needed_for_changes_resizes_and_frames() {
+        let store = new_state_store();
+        let mut runtime = Runtime::new(store.clone(), Duration::from_millis(1)).unwrap();
+        // never rendered
+        assert!(runtime.needs_render());
+        assert!(!runtime.needs_render());
+        assert_eq!(runtime.deadline(), None);
+
+        store.borrow_mut().request_render();
+        assert!(runtime.needs_render());
+        render_app_to_graph(&El::<()>::None, store.clone());
+        assert!(!runtime.needs_render());
+
+        runtime.clock().request_frame();
+        assert_eq!(runtime.deadline(), Some(runtime.vsync.next_frame()));
+        assert!(runtime.needs_render());
+        assert!(!runtime.needs_render());
+
+        runtime.redraw = true;
+        assert!(runtime.needs_render());
+        assert!(!runtime.needs_render());
+    }
+
+    #[test]
+    fn idle_runtimes_wait_for_the_timers() {
+        let store = new_state_store();
+        let mut runtime = Runtime::new(store.clone(), Duration::from_millis(1)).unwrap();
+        runtime.needs_render();
+        let handle = ComponentHandle::new("/0~Timer", &store);
+        handle.set_timeout(Duration::from_secs(60), |_| {});
+        let due = store.borrow().scheduler().borrow().next_due();
+        assert!(due.is_some());
+        assert_eq!(runtime.deadline(), due);
+    }
+}
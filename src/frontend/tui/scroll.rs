@@ -0,0 +1,255 @@
+use std::any::Any;
+use std::rc::Rc;
+
+use termion::event::Key;
+
+use crate::engine::*;
+use crate::frontend::tui::*;
+
+pub struct ScrollViewProps {
+    pub pos: Position,
+    pub dim: Dimension,
+    // height of the content in lines; bounds the scroll offset
+    pub content_height: u16,
+    pub scrollbar: bool,
+    // lines scrolled per mouse wheel notch
+    pub wheel_step: u16,
+}
+
+impl Default for ScrollViewProps {
+    fn default() -> Self {
+        ScrollViewProps {
+            pos: Position {
+                ..Default::default()
+            },
+            dim: Dimension {
+                ..Default::default()
+            },
+            content_height: 0,
+            scrollbar: true,
+            wheel_step: 1,
+        }
+    }
+}
+
+// Clips its content to its box and scrolls it vertically.
+// The content is laid out at absolute positions, as if it was not scrolled.
+pub struct ScrollView {
+    pub props: ScrollViewProps,
+    pub content: Box<dyn Fn() -> El<TUINode>>,
+}
+
+#[derive(Clone)]
+struct ScrollViewState {
+    offset: u16,
+}
+
+pub fn scroll_view(
+    props: ScrollViewProps,
+    content: impl Fn() -> El<TUINode> + 'static,
+) -> El<TUINode> {
+    El::Component(Box::new(ScrollView {
+        props,
+        content: Box::new(content),
+    }))
+}
+
+impl ScrollView {
+    fn max_offset(&self) -> u16 {
+        self.props
+            .content_height
+            .saturating_sub(self.props.dim.height)
+    }
+
+    fn has_scrollbar(&self) -> bool {
+        self.props.scrollbar
+            && self.max_offset() > 0
+            && self.props.dim.width > 1
+            && self.props.dim.height > 0
+    }
+
+    fn render_scrollbar(&self, offset: u16) -> El<TUINode> {
+        let height = self.props.dim.height;
+        let content_height = self.props.content_height;
+        let max_offset = self.max_offset();
+
+        let thumb_height =
+            ((u32::from(height) * u32::from(height) / u32::from(content_height)) as u16).max(1);
+        let thumb_top = (u32::from(offset) * u32::from(height.saturating_sub(thumb_height))
+            / u32::from(max_offset)) as u16;
+
+        let left = self.props.pos.left + self.props.dim.width - 1;
+        El::Container(
+            (0..height)
+                .map(|line| {
+                    let thumb = line >= thumb_top && line < thumb_top + thumb_height;
+                    El::Node(Node::new(
+                        TUINode::new(left, self.props.pos.top + line)
+                            .set_text(Some(if thumb { "█" } else { "░" }.to_string())),
+                    ))
+                })
+                .collect(),
+        )
+    }
+}
+
+impl StateReceiver<ScrollViewState> for ScrollView {}
+impl Component<TUINode> for ScrollView {
+    fn initial_state(&self) -> Rc<dyn Any> {
+        Rc::new(ScrollViewState { offset: 0 })
+    }
+
+    fn render(&self, state: Rc<BoxedState>, set_state: Rc<SetState>) -> El<TUINode> {
+        let state = self.must_receive_state(state);
+        let max_offset = self.max_offset();
+        // content may have shrunk since the offset was stored
+        let offset = state.offset.min(max_offset);
+
+        let width = if self.has_scrollbar() {
+            self.props.dim.width - 1
+        } else {
+            self.props.dim.width
+        };
+
+        let viewport = El::Node(
+            Node::new(
+                TUINode::new(self.props.pos.left, self.props.pos.top)
+                    .set_dimension(width, self.props.dim.height)
+                    .set_clip(true)
+                    .set_scroll_offset(0, offset)
+                    .set_on_wheel(Some(handle_on_wheel(
+                        offset,
+                        max_offset,
+                        self.props.wheel_step,
+                        set_state.clone(),
+                    )))
+                    .set_on_key(Some(handle_on_key(
+                        offset,
+                        max_offset,
+                        self.props.dim.height,
+                        set_state,
+                    ))),
+            )
+            .add_child((self.content)()),
+        );
+
+        if self.has_scrollbar() {
+            El::Container(vec![viewport, self.render_scrollbar(offset)])
+        } else {
+            viewport
+        }
+    }
+}
+
+fn handle_on_wheel(
+    offset: u16,
+    max_offset: u16,
+    step: u16,
+    set_state: Rc<SetState>,
+) -> MouseHandler {
+    Rc::new(move |event: &mut UIEvent| {
+        let new_offset = match event.action {
+            MouseAction::Wheel(WheelDirection::Up) => offset.saturating_sub(step),
+            MouseAction::Wheel(WheelDirection::Down) => offset.saturating_add(step).min(max_offset),
+            _ => return None,
+        };
+        // enclosing scroll views stay still
+        event.stop_propagation();
+        set_state(Rc::new(ScrollViewState { offset: new_offset }));
+        None
+    })
+}
+
+fn handle_on_key(offset: u16, max_offset: u16, page: u16, set_state: Rc<SetState>) -> KeyHandler {
+    Rc::new(move |event: &mut KeyEvent| {
+        let new_offset = match event.key {
+            Key::PageUp => offset.saturating_sub(page),
+            Key::PageDown => offset.saturating_add(page).min(max_offset),
+            _ => return None,
+        };
+        event.stop_propagation();
+        set_state(Rc::new(ScrollViewState { offset: new_offset }));
+        None
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::tui::widgets::testing::Harness;
+
+    // 10 lines in a view of 4: the offset goes up to 6
+    fn harness(height: u16, scrollbar: bool) -> Harness {
+        Harness::new(move || {
+            scroll_view(
+                ScrollViewProps {
+                    pos: Position { left: 1, top: 1 },
+                    dim: Dimension { width: 10, height },
+                    content_height: 10,
+                    scrollbar,
+                    wheel_step: 4,
+                },
+                || {
+                    El::Container(
+                        (1..=10)
+                            .map(|top| {
+                                El::Node(Node::new(
+                                    TUINode::new(1, top).set_text(Some(top.to_string())),
+                                ))
+                            })
+                            .collect(),
+                    )
+                },
+            )
+        })
+    }
+
+    fn offset(h: &Harness) -> u16 {
+        match &h.graph {
+            Some(RenderedEl::Node(viewport)) => viewport.payload.scroll_offset.top,
+            _ => panic!("no viewport"),
+        }
+    }
+
+    #[test]
+    fn wheel_scrolls_within_the_content() {
+        let mut h = harness(4, false);
+        h.wheel(2, 2, WheelDirection::Down);
+        assert_eq!(offset(&h), 4);
+        h.wheel(2, 2, WheelDirection::Down);
+        assert_eq!(offset(&h), 6);
+        h.wheel(2, 2, WheelDirection::Up);
+        assert_eq!(offset(&h), 2);
+        h.wheel(2, 2, WheelDirection::Up);
+        assert_eq!(offset(&h), 0);
+    }
+
+    #[test]
+    fn pages_scroll_within_the_content() {
+        let mut h = harness(4, false);
+        h.press(Key::PageDown);
+        assert_eq!(offset(&h), 4);
+        h.press(Key::PageDown);
+        assert_eq!(offset(&h), 6);
+        h.press(Key::PageUp);
+        assert_eq!(offset(&h), 2);
+        h.press(Key::PageUp);
+        assert_eq!(offset(&h), 0);
+    }
+
+    #[test]
+    fn scrollbars_show_the_offset() {
+        let mut h = harness(4, true);
+        // the thumb is 4 * 4 / 10 lines high
+        assert_eq!(&h.texts()[10..], ["█", "░", "░", "░"]);
+        h.press(Key::PageDown);
+        h.press(Key::PageDown);
+        assert_eq!(&h.texts()[10..], ["░", "░", "░", "█"]);
+    }
+
+    #[test]
+    fn empty_views_have_no_scrollbar() {
+        let h = harness(0, true);
+        assert_eq!(h.texts().len(), 10);
+    }
+}
@@ -0,0 +1,187 @@
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use termion::event::Key;
+
+use crate::engine::*;
+use crate::frontend::tui::overlay::*;
+use crate::frontend::tui::widgets::*;
+use crate::frontend::tui::*;
+
+pub struct TimeTravelProps {
+    pub pos: Position,
+    pub dim: Dimension,
+    // with its history enabled
+    pub state_store: Rc<RefCell<StateStore>>,
+    // shows the states of the registered components
+    pub registry: Option<Rc<StateRegistry>>,
+    // opens and closes the panel, unless a focused node consumes it
+    pub toggle_key: Key,
+}
+
+impl TimeTravelProps {
+    // The panel browses the history of the store of the app: there is no default
+    pub fn new(state_store: Rc<RefCell<StateStore>>) -> Self {
+        TimeTravelProps {
+            pos: Position { left: 1, top: 1 },
+            dim: Dimension {
+                width: 70,
+                height: 12,
+            },
+            state_store,
+            registry: None,
+            toggle_key: Key::Char('t'),
+        }
+    }
+}
+
+// Panel browsing the history of the states while the app runs: Left and Right
+// undo and redo a change, Home and End go to the oldest and latest states.
+// The app is rendered with the states of the position browsed.
+pub struct TimeTravel {
+    pub props: TimeTravelProps,
+}
+
+#[derive(Clone)]
+struct TimeTravelState {
+    // not a state of the store, to stay out of the history
+    open: Rc<Cell<bool>>,
+}
+
+pub fn time_travel(props: TimeTravelProps) -> El<TUINode> {
+    El::Component(Box::new(TimeTravel { props }))
+}
+
+impl StateReceiver<TimeTravelState> for TimeTravel {}
+impl Component<TUINode> for TimeTravel {
+    fn initial_state(&self) -> Rc<dyn Any> {
+        Rc::new(TimeTravelState {
+            open: Rc::new(Cell::new(false)),
+        })
+    }
+
+    fn render(&self, state: Rc<BoxedState>, _: Rc<SetState>) -> El<TUINode> {
+        let props = &self.props;
+        let state = self.must_receive_state(state);
+
+        let on_key = handle_on_key(
+            Rc::clone(&props.state_store),
+            Rc::clone(&state.open),
+            props.toggle_key,
+        );
+        let listener = El::Node(Node::new(
+            TUINode::new(1, 1)
+                .set_dimension(0, 0)
+                .set_on_key(Some(on_key)),
+        ));
+        if !state.open.get() {
+            return listener;
+        }
+
+        El::Container(vec![listener, overlay(self.render_panel())])
+    }
+}
+
+impl TimeTravel {
+    fn render_panel(&self) -> El<TUINode> {
+        let props = &self.props;
+        let store = props.state_store.borrow();
+        let (left, top) = (props.pos.left, props.pos.top);
+        let width = props.dim.width.saturating_sub(4) as usize;
+        let line = |top: u16, text: String| {
+            El::Node(Node::new(
+                TUINode::new(left + 2, top).set_text(Some(align_text(&text, width, Align::Left))),
+            ))
+        };
+
+        let mut lines = Vec::new();
+        match store.history() {
+            None => lines.push(line(top + 2, "History not enabled".to_string())),
+            Some(history) => {
+                lines.push(line(
+                    top + 1,
+                    format!(
+                        "History {}/{}   \u{2190}/\u{2192} step   Home/End",
+                        history.position(),
+                        history.len()
+                    ),
+                ));
+
+                // the window of changes keeps the last one applied in view
+                let rows = props.dim.height.saturating_sub(4) as usize;
+                let end = (history.position() + rows / 2).max(rows).min(history.len());
+                let start = end.saturating_sub(rows);
+                let origin = history.get(0).map(|c| c.at);
+
+                for (row, (i, change)) in history
+                    .changes()
+                    .enumerate()
+                    .skip(start)
+                    .take(rows)
+                    .enumerate()
+                {
+                    let since = origin.map_or(0.0, |o| change.at.duration_since(o).as_secs_f64());
+                    let text = format!(
+                        "{:>4} +{:>6.1}s {} {}",
+                        i + 1,
+                        since,
+                        truncate_start(&change.path, width / 3),
+                        self.describe(&change.path, &change.new)
+                    );
+                    let node = TUINode::new(left + 2, top + 3 + row as u16)
+                        .set_text(Some(align_text(&text, width, Align::Left)))
+                        // the last change applied
+                        .set_inverse(i + 1 == history.position());
+                    lines.push(El::Node(Node::new(node)));
+                }
+            }
+        }
+
+        El::Node(
+            Node::new(
+                TUINode::new(left, top)
+                    .set_dimension(props.dim.width, props.dim.height)
+                    .set_border(true)
+                    .set_fill(true),
+            )
+            .add_children(lines),
+        )
+    }
+
+    fn describe(&self, path: &str, state: &Rc<dyn Any>) -> String {
+        self.props
+            .registry
+            .as_ref()
+            .and_then(|r| r.describe(path, state.as_ref()))
+            .unwrap_or_else(|| "(state)".to_string())
+    }
+}
+
+fn handle_on_key(
+    state_store: Rc<RefCell<StateStore>>,
+    open: Rc<Cell<bool>>,
+    toggle_key: Key,
+) -> KeyHandler {
+    Rc::new(move |event: &mut KeyEvent| {
+        let mut store = state_store.borrow_mut();
+        if event.key == toggle_key {
+            open.set(!open.get());
+        } else if !open.get() {
+            return None;
+        } else {
+            let end = store.history().map_or(0, |h| h.len());
+            match event.key {
+                Key::Left => store.history_back(),
+                Key::Right => store.history_forward(),
+                Key::Home => store.history_go(0),
+                Key::End => store.history_go(end),
+                _ => return None,
+            };
+        }
+
+        event.stop_propagation();
+        store.request_render();
+        None
+    })
+}
@@ -0,0 +1,90 @@
+use std::rc::Rc;
+
+use crate::engine::*;
+use crate::frontend::tui::widgets::text_width;
+use crate::frontend::tui::*;
+
+pub struct ButtonProps {
+    pub pos: Position,
+    pub title: String,
+    // None: fits the title
+    pub dim: Option<Dimension>,
+    pub on_click: Option<MouseHandler>,
+    pub disabled: bool,
+}
+
+impl Default for ButtonProps {
+    fn default() -> Self {
+        ButtonProps {
+            pos: Position {
+                ..Default::default()
+            },
+            title: "".to_string(),
+            dim: None,
+            on_click: None,
+            disabled: false,
+        }
+    }
+}
+
+pub struct Button {
+    pub props: ButtonProps,
+}
+
+pub fn button(props: ButtonProps) -> El<TUINode> {
+    El::Component(Box::new(Button { props }))
+}
+
+impl Component<TUINode> for Button {
+    fn render(&self, _: Rc<BoxedState>, _: Rc<SetState>) -> El<TUINode> {
+        let props = &self.props;
+        let (width, height) = match &props.dim {
+            Some(dim) => (dim.width, dim.height),
+            None => (text_width(&props.title) as u16 + 6, 3),
+        };
+
+        El::Node(Node::new(
+            TUINode::new(props.pos.left, props.pos.top)
+                .set_text(Some(props.title.clone()))
+                .set_border(true)
+                .set_dimension(width, height)
+                .disable(props.disabled)
+                .set_on_click(props.on_click.clone()),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::tui::widgets::testing::Harness;
+
+    fn harness(disabled: bool) -> Harness {
+        Harness::new(move || {
+            button(ButtonProps {
+                pos: Position { left: 2, top: 2 },
+                title: "日本".to_string(),
+                on_click: Some(Rc::new(|_: &mut UIEvent| message("clicked"))),
+                disabled,
+                ..Default::default()
+            })
+        })
+    }
+
+    #[test]
+    fn clicks_call_on_click() {
+        let mut h = harness(false);
+        // 4 cells of title, plus the border and the padding
+        h.click(11, 4);
+        assert_eq!(h.input.take_messages::<&str>(), ["clicked"]);
+        h.click(12, 4);
+        assert!(h.input.take_messages::<&str>().is_empty());
+    }
+
+    #[test]
+    fn disabled_buttons_are_not_clicked() {
+        let mut h = harness(true);
+        h.click(3, 3);
+        assert!(h.input.take_messages::<&str>().is_empty());
+    }
+}
@@ -0,0 +1,563 @@
+use std::rc::Rc;
+
+use crate::engine::*;
+use crate::frontend::tui::widgets::{align_text, Align};
+use crate::frontend::tui::*;
+
+// Bottom aligned blocks, by eighths of cell
+const LOWER_BLOCKS: [char; 9] = [' ', '▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+// Bit of each dot of a Braille cell, by column then row
+const BRAILLE_DOTS: [[u8; 4]; 2] = [[0x01, 0x02, 0x04, 0x40], [0x08, 0x10, 0x20, 0x80]];
+
+// Lowest and highest values of the scale: the given ones, or else those of the data
+fn value_range(
+    data: impl Iterator<Item = f64>,
+    min: Option<f64>,
+    max: Option<f64>,
+    from_zero: bool,
+) -> (f64, f64) {
+    let (lo, hi) = data
+        .filter(|v| v.is_finite())
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+            (lo.min(v), hi.max(v))
+        });
+    // no data
+    let (lo, hi) = if lo > hi { (0.0, 1.0) } else { (lo, hi) };
+
+    let lo = min.unwrap_or(if from_zero { lo.min(0.0) } else { lo });
+    let hi = max.unwrap_or(hi);
+    if hi > lo {
+        (lo, hi)
+    } else {
+        (lo, lo + 1.0)
+    }
+}
+
+// Share of the scale below value, clamped to 0..=1
+fn ratio(value: f64, (lo, hi): (f64, f64)) -> f64 {
+    match value.is_finite() {
+        true => ((value - lo) / (hi - lo)).clamp(0.0, 1.0),
+        false => 0.0,
+    }
+}
+
+// Cell of a vertical bar of height eighths of cell, on row 0 at the bottom
+fn bar_cell(eighths: u32, row: u16) -> char {
+    let filled = eighths.saturating_sub(u32::from(row) * 8).min(8);
+    LOWER_BLOCKS[filled as usize]
+}
+
+// Short text of a value for the axes
+pub fn format_value(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        format!("{:.0}", value)
+    } else {
+        format!("{:.1}", value)
+    }
+}
+
+// Vertical axis with the range bounds as labels, left of a plot area of height
+// rows. Returns the axis and its width.
+fn y_axis(pos: Position, height: u16, range: (f64, f64)) -> (El<TUINode>, u16) {
+    let top = format_value(range.1);
+    let bottom = format_value(range.0);
+    let label_width = top.chars().count().max(bottom.chars().count());
+
+    let rows = (0..height)
+        .map(|row| {
+            let text = match row {
+                0 => format!("{:>w$}┤", top, w = label_width),
+                r if r + 1 == height => format!("{:>w$}┤", bottom, w = label_width),
+                _ => format!("{:>w$}│", "", w = label_width),
+            };
+            El::Node(Node::new(
+                TUINode::new(pos.left, pos.top + row).set_text(Some(text)),
+            ))
+        })
+        .collect();
+
+    (El::Container(rows), label_width as u16 + 1)
+}
+
+// Horizontal axis under a plot area of width cells, starting at the corner
+fn x_axis(pos: Position, width: u16) -> El<TUINode> {
+    El::Node(Node::new(
+        TUINode::new(pos.left, pos.top).set_text(Some(format!("└{}", "─".repeat(width.into())))),
+    ))
+}
+
+pub struct SparklineProps {
+    pub pos: Position,
+    // the last width values are shown, one per cell
+    pub width: u16,
+    pub height: u16,
+    pub data: Vec<f64>,
+    // None: scaled from 0, or the lowest value if negative, to the highest value
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+impl Default for SparklineProps {
+    fn default() -> Self {
+        SparklineProps {
+            pos: Position {
+                ..Default::default()
+            },
+            width: 20,
+            height: 1,
+            data: Vec::new(),
+            min: None,
+            max: None,
+        }
+    }
+}
+
+// Compact chart of a series, without axes
+pub struct Sparkline {
+    pub props: SparklineProps,
+}
+
+pub fn sparkline(props: SparklineProps) -> El<TUINode> {
+    El::Component(Box::new(Sparkline { props }))
+}
+
+impl Component<TUINode> for Sparkline {
+    fn render(&self, _: Rc<BoxedState>, _: Rc<SetState>) -> El<TUINode> {
+        let props = &self.props;
+        let shown = &props.data[props.data.len().saturating_sub(props.width.into())..];
+        let range = value_range(shown.iter().copied(), props.min, props.max, true);
+        let eighths: Vec<u32> = shown
+            .iter()
+            .map(|v| (ratio(*v, range) * f64::from(props.height) * 8.0).round() as u32)
+            .collect();
+
+        El::Container(
+            (0..props.height)
+                .map(|line| {
+                    let row = props.height - 1 - line;
+                    El::Node(Node::new(
+                        TUINode::new(props.pos.left, props.pos.top + line)
+                            .set_dimension(props.width, 1)
+                            .set_text(Some(eighths.iter().map(|e| bar_cell(*e, row)).collect())),
+                    ))
+                })
+                .collect(),
+        )
+    }
+}
+
+pub struct BarChartProps {
+    pub pos: Position,
+    // axes and labels included
+    pub dim: Dimension,
+    // label and value of each bar; negative values are drawn as 0
+    pub bars: Vec<(String, f64)>,
+    pub bar_width: u16,
+    pub gap: u16,
+    // None: the highest value
+    pub max: Option<f64>,
+}
+
+impl Default for BarChartProps {
+    fn default() -> Self {
+        BarChartProps {
+            pos: Position {
+                ..Default::default()
+            },
+            dim: Dimension {
+                ..Default::default()
+            },
+            bars: Vec::new(),
+            bar_width: 3,
+            gap: 1,
+            max: None,
+        }
+    }
+}
+
+// Vertical bars with their labels below them
+pub struct BarChart {
+    pub props: BarChartProps,
+}
+
+pub fn bar_chart(props: BarChartProps) -> El<TUINode> {
+    El::Component(Box::new(BarChart { props }))
+}
+
+impl Component<TUINode> for BarChart {
+    fn render(&self, _: Rc<BoxedState>, _: Rc<SetState>) -> El<TUINode> {
+        let props = &self.props;
+        // the horizontal axis and the labels take the last two rows
+        let height = props.dim.height.saturating_sub(2);
+        let range = value_range(
+            props.bars.iter().map(|(_, v)| *v),
+            Some(0.0),
+            props.max,
+            true,
+        );
+        let (axis, axis_width) = y_axis(props.pos, height, range);
+        let left = props.pos.left + axis_width;
+        let width = props.dim.width.saturating_sub(axis_width);
+        let gap = " ".repeat(props.gap.into());
+
+        let eighths: Vec<u32> = props
+            .bars
+            .iter()
+            .map(|(_, v)| (ratio(*v, range) * f64::from(height) * 8.0).round() as u32)
+            .collect();
+        let rows = (0..height).map(|line| {
+            let row = height - 1 - line;
+            let text: String = eighths
+                .iter()
+                .map(|e| {
+                    let cell = bar_cell(*e, row).to_string();
+                    format!("{}{}", cell.repeat(props.bar_width.into()), gap)
+                })
+                .collect();
+            El::Node(Node::new(
+                TUINode::new(left, props.pos.top + line).set_text(Some(align_text(
+                    &text,
+                    width.into(),
+                    Align::Left,
+                ))),
+            ))
+        });
+
+        let labels: String = props
+            .bars
+            .iter()
+            .map(|(label, _)| {
+                format!(
+                    "{}{}",
+                    align_text(label, props.bar_width.into(), Align::Center),
+                    gap
+                )
+            })
+            .collect();
+
+        El::Container(
+            vec![
+                axis,
+                x_axis(
+                    Position {
+                        left: left - 1,
+                        top: props.pos.top + height,
+                    },
+                    width,
+                ),
+                El::Node(Node::new(
+                    TUINode::new(left, props.pos.top + height + 1).set_text(Some(align_text(
+                        &labels,
+                        width.into(),
+                        Align::Left,
+                    ))),
+                )),
+            ]
+            .into_iter()
+            .chain(rows)
+            .collect(),
+        )
+    }
+}
+
+pub struct LineChartProps {
+    pub pos: Position,
+    // axes and labels included
+    pub dim: Dimension,
+    // each series is drawn over the whole width, overlaid on the others
+    pub series: Vec<Vec<f64>>,
+    // spread evenly under the horizontal axis; none leaves out the row
+    pub x_labels: Vec<String>,
+    // None: the bounds of the data
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+impl Default for LineChartProps {
+    fn default() -> Self {
+        LineChartProps {
+            pos: Position {
+                ..Default::default()
+            },
+            dim: Dimension {
+                ..Default::default()
+            },
+            series: Vec::new(),
+            x_labels: Vec::new(),
+            min: None,
+            max: None,
+        }
+    }
+}
+
+// Lines through the values of series, drawn with Braille dots: 2 by 4 per cell
+pub struct LineChart {
+    pub props: LineChartProps,
+}
+
+pub fn line_chart(props: LineChartProps) -> El<TUINode> {
+    El::Component(Box::new(LineChart { props }))
+}
+
+// Grid of Braille cells, drawn dot by dot
+struct BrailleCanvas {
+    width: usize,
+    height: usize,
+    cells: Vec<u8>,
+}
+
+impl BrailleCanvas {
+    fn new(width: u16, height: u16) -> Self {
+        let (width, height) = (usize::from(width), usize::from(height));
+        BrailleCanvas {
+            width,
+            height,
+            cells: vec![0; width * height],
+        }
+    }
+
+    fn set(&mut self, x: i64, y: i64) {
+        if x < 0 || y < 0 || x >= self.width as i64 * 2 || y >= self.height as i64 * 4 {
+            return;
+        }
+        let (x, y) = (x as usize, y as usize);
+        self.cells[y / 4 * self.width + x / 2] |= BRAILLE_DOTS[x % 2][y % 4];
+    }
+
+    // Bresenham's line
+    fn line(&mut self, (x0, y0): (i64, i64), (x1, y1): (i64, i64)) {
+        let (dx, dy) = ((x1 - x0).abs(), -(y1 - y0).abs());
+        let (sx, sy) = ((x1 - x0).signum(), (y1 - y0).signum());
+        let (mut x, mut y, mut err) = (x0, y0, dx + dy);
+        loop {
+            self.set(x, y);
+            if x == x1 && y == y1 {
+                return;
+            }
+            let e2 = 2 * err;
+            if e2 >= dy {
+                err += dy;
+                x += sx;
+            }
+            if e2 <= dx {
+                err += dx;
+                y += sy;
+            }
+        }
+    }
+
+    fn row(&self, row: usize) -> String {
+        self.cells[row * self.width..(row + 1) * self.width]
+            .iter()
+            .map(|bits| match bits {
+                0 => ' ',
+                b => char::from_u32(0x2800 + u32::from(*b)).unwrap_or(' '),
+            })
+            .collect()
+    }
+}
+
+impl Component<TUINode> for LineChart {
+    fn render(&self, _: Rc<BoxedState>, _: Rc<SetState>) -> El<TUINode> {
+        let props = &self.props;
+        let label_rows = if props.x_labels.is_empty() { 0 } else { 1 };
+        let height = props.dim.height.saturating_sub(1 + label_rows);
+        let range = value_range(
+            props.series.iter().flatten().copied(),
+            props.min,
+            props.max,
+            false,
+        );
+        let (axis, axis_width) = y_axis(props.pos, height, range);
+        let left = props.pos.left + axis_width;
+        let width = props.dim.width.saturating_sub(axis_width);
+
+        let mut canvas = BrailleCanvas::new(width, height);
+        let dots_width = f64::from(width) * 2.0 - 1.0;
+        let dots_height = f64::from(height) * 4.0 - 1.0;
+        for series in &props.series {
+            let last = series.len().saturating_sub(1).max(1) as f64;
+            let dot = |i: usize, v: f64| {
+                (
+                    (i as f64 * dots_width / last).round() as i64,
+                    ((1.0 - ratio(v, range)) * dots_height).round() as i64,
+                )
+            };
+            // missing values break the line
+            let points: Vec<Option<(i64, i64)>> = series
+                .iter()
+                .enumerate()
+                .map(|(i, v)| v.is_finite().then(|| dot(i, *v)))
+                .collect();
+            for (i, point) in points.iter().enumerate() {
+                match (point, points.get(i + 1)) {
+                    (Some(from), Some(Some(to))) => canvas.line(*from, *to),
+                    (Some(p), _) => canvas.set(p.0, p.1),
+                    _ => {}
+                }
+            }
+        }
+
+        let rows = (0..height).map(|row| {
+            El::Node(Node::new(
+                TUINode::new(left, props.pos.top + row)
+                    .set_dimension(width, 1)
+                    .set_text(Some(canvas.row(row.into()))),
+            ))
+        });
+
+        let labels = props.x_labels.iter().enumerate().map(|(i, label)| {
+            let last = props.x_labels.len().saturating_sub(1).max(1);
+            let len = label.chars().count() as u16;
+            // centered on its position, within the plot area
+            let at = (i * usize::from(width.saturating_sub(1)) / last) as u16;
+            let at = at.saturating_sub(len / 2).min(width.saturating_sub(len));
+            El::Node(Node::new(
+                TUINode::new(left + at, props.pos.top + height + 1).set_text(Some(label.clone())),
+            ))
+        });
+
+        El::Container(
+            vec![
+                axis,
+                x_axis(
+                    Position {
+                        left: left - 1,
+                        top: props.pos.top + height,
+                    },
+                    width,
+                ),
+            ]
+            .into_iter()
+            .chain(rows)
+            .chain(labels)
+            .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::tui::widgets::testing::Harness;
+
+    #[test]
+    fn ranges_span_the_data() {
+        let data = [2.0, f64::NAN, 5.0, 3.0];
+        assert_eq!(
+            value_range(data.iter().copied(), None, None, false),
+            (2.0, 5.0)
+        );
+        assert_eq!(
+            value_range(data.iter().copied(), None, None, true),
+            (0.0, 5.0)
+        );
+        assert_eq!(
+            value_range(data.iter().copied(), Some(-1.0), Some(10.0), false),
+            (-1.0, 10.0)
+        );
+    }
+
+    #[test]
+    fn ranges_are_never_empty() {
+        assert_eq!(
+            value_range([4.0, 4.0].iter().copied(), None, None, false),
+            (4.0, 5.0)
+        );
+        assert_eq!(
+            value_range([].iter().copied(), None, None, false),
+            (0.0, 1.0)
+        );
+        assert_eq!(
+            value_range([3.0].iter().copied(), None, Some(1.0), false),
+            (3.0, 4.0)
+        );
+    }
+
+    #[test]
+    fn bars_fill_cells_by_eighths() {
+        // 1.5 cells
+        assert_eq!(bar_cell(12, 0), '█');
+        assert_eq!(bar_cell(12, 1), '▄');
+        assert_eq!(bar_cell(12, 2), ' ');
+        assert_eq!(bar_cell(3, 0), '▃');
+        assert_eq!(bar_cell(0, 0), ' ');
+    }
+
+    #[test]
+    fn canvases_draw_lines_dot_by_dot() {
+        let mut canvas = BrailleCanvas::new(2, 1);
+        canvas.line((0, 0), (3, 3));
+        assert_eq!(canvas.row(0), "⠑⢄");
+
+        let mut canvas = BrailleCanvas::new(2, 1);
+        canvas.line((0, 3), (3, 3));
+        // out of the canvas
+        canvas.set(4, 0);
+        canvas.set(-1, 0);
+        assert_eq!(canvas.row(0), "⣀⣀");
+    }
+
+    #[test]
+    fn sparklines_scale_from_zero() {
+        let h = Harness::new(|| {
+            sparkline(SparklineProps {
+                width: 9,
+                data: (-2..=8).map(f64::from).collect(),
+                min: Some(0.0),
+                ..Default::default()
+            })
+        });
+        // the first values are not shown
+        assert_eq!(h.texts(), [" ▁▂▃▄▅▆▇█"]);
+    }
+
+    #[test]
+    fn bar_charts_draw_bars_over_their_labels() {
+        let h = Harness::new(|| {
+            bar_chart(BarChartProps {
+                dim: Dimension {
+                    width: 6,
+                    height: 4,
+                },
+                bars: vec![("a".to_string(), 2.0), ("b".to_string(), 4.0)],
+                bar_width: 1,
+                ..Default::default()
+            })
+        });
+        assert_eq!(h.texts(), ["4┤", "0┤", "└────", "a b ", "  █ ", "█ █ "]);
+    }
+
+    #[test]
+    fn line_charts_join_the_values() {
+        let h = Harness::new(|| {
+            line_chart(LineChartProps {
+                dim: Dimension {
+                    width: 4,
+                    height: 4,
+                },
+                series: vec![vec![0.0, 1.0]],
+                x_labels: vec!["a".to_string(), "b".to_string()],
+                ..Default::default()
+            })
+        });
+        assert_eq!(h.texts(), ["1┤", "0┤", "└──", " ⡜", "⡜ ", "a", "b"]);
+    }
+
+    #[test]
+    fn axis_labels_are_right_aligned() {
+        let h = Harness::new(|| {
+            line_chart(LineChartProps {
+                dim: Dimension {
+                    width: 8,
+                    height: 4,
+                },
+                min: Some(-5.5),
+                max: Some(10.0),
+                ..Default::default()
+            })
+        });
+        assert_eq!(&h.texts()[..4], ["  10┤", "    │", "-5.5┤", "└───"]);
+    }
+}
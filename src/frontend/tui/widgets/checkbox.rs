@@ -0,0 +1,127 @@
+use std::any::Any;
+use std::rc::Rc;
+
+use crate::engine::*;
+use crate::frontend::tui::widgets::{text_width, ChangeHandler};
+use crate::frontend::tui::*;
+
+pub struct CheckboxProps {
+    pub pos: Position,
+    pub label: String,
+    // initial value; the current one is kept in the component state
+    pub checked: bool,
+    pub on_change: Option<ChangeHandler<bool>>,
+    pub disabled: bool,
+}
+
+impl Default for CheckboxProps {
+    fn default() -> Self {
+        CheckboxProps {
+            pos: Position {
+                ..Default::default()
+            },
+            label: "".to_string(),
+            checked: false,
+            on_change: None,
+            disabled: false,
+        }
+    }
+}
+
+pub struct Checkbox {
+    pub props: CheckboxProps,
+}
+
+#[derive(Clone)]
+struct CheckboxState {
+    checked: bool,
+}
+
+pub fn checkbox(props: CheckboxProps) -> El<TUINode> {
+    El::Component(Box::new(Checkbox { props }))
+}
+
+impl StateReceiver<CheckboxState> for Checkbox {}
+impl Component<TUINode> for Checkbox {
+    fn initial_state(&self) -> Rc<dyn Any> {
+        Rc::new(CheckboxState {
+            checked: self.props.checked,
+        })
+    }
+
+    fn render(&self, state: Rc<BoxedState>, set_state: Rc<SetState>) -> El<TUINode> {
+        let state = self.must_receive_state(state);
+        let props = &self.props;
+        let text = format!(
+            "[{}] {}",
+            if state.checked { "x" } else { " " },
+            props.label
+        );
+
+        El::Node(Node::new(
+            TUINode::new(props.pos.left, props.pos.top)
+                .set_dimension(text_width(&text) as u16, 1)
+                .set_text(Some(text))
+                .disable(props.disabled)
+                .set_on_click(Some(handle_on_click(
+                    !state.checked,
+                    set_state,
+                    props.on_change.clone(),
+                ))),
+        ))
+    }
+}
+
+fn handle_on_click(
+    checked: bool,
+    set_state: Rc<SetState>,
+    on_change: Option<ChangeHandler<bool>>,
+) -> MouseHandler {
+    Rc::new(move |_: &mut UIEvent| {
+        set_state(Rc::new(CheckboxState { checked }));
+        on_change.as_ref().and_then(|h| h(checked))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::tui::widgets::testing::Harness;
+
+    fn harness(disabled: bool) -> Harness {
+        Harness::new(move || {
+            checkbox(CheckboxProps {
+                pos: Position { left: 2, top: 3 },
+                label: "Check".to_string(),
+                on_change: Some(Rc::new(|checked| Some(Box::new(checked)))),
+                disabled,
+                ..Default::default()
+            })
+        })
+    }
+
+    #[test]
+    fn clicks_toggle_the_check() {
+        let mut h = harness(false);
+        assert_eq!(h.texts(), ["[ ] Check"]);
+        h.click(3, 3);
+        assert_eq!(h.texts(), ["[x] Check"]);
+        assert_eq!(h.input.take_messages::<bool>(), [true]);
+        h.click(10, 3);
+        assert_eq!(h.texts(), ["[ ] Check"]);
+        assert_eq!(h.input.take_messages::<bool>(), [false]);
+    }
+
+    #[test]
+    fn clicks_out_of_it_or_disabled_are_ignored() {
+        let mut h = harness(false);
+        h.click(11, 3);
+        h.click(3, 4);
+        assert_eq!(h.texts(), ["[ ] Check"]);
+
+        let mut h = harness(true);
+        h.click(3, 3);
+        assert_eq!(h.texts(), ["[ ] Check"]);
+        assert!(h.input.take_messages::<bool>().is_empty());
+    }
+}
@@ -0,0 +1,181 @@
+use std::rc::Rc;
+
+use crate::engine::*;
+use crate::frontend::tui::*;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+pub struct LabelProps {
+    pub pos: Position,
+    pub text: String,
+    // None: as wide as the text; otherwise the text is aligned and truncated
+    pub width: Option<u16>,
+    pub align: Align,
+    pub disabled: bool,
+}
+
+impl Default for LabelProps {
+    fn default() -> Self {
+        LabelProps {
+            pos: Position {
+                ..Default::default()
+            },
+            text: "".to_string(),
+            width: None,
+            align: Align::Left,
+            disabled: false,
+        }
+    }
+}
+
+pub struct Label {
+    pub props: LabelProps,
+}
+
+pub fn label(props: LabelProps) -> El<TUINode> {
+    El::Component(Box::new(Label { props }))
+}
+
+// Cells a char takes in a terminal: 2 for the wide East Asian chars and the
+// emoji, 0 for the combining marks and zero width chars
+pub fn char_width(c: char) -> usize {
+    match c as u32 {
+        0x0300..=0x036F | 0x200B..=0x200F | 0xFE00..=0xFE0F => 0,
+        0x1100..=0x115F
+        | 0x2E80..=0x303E
+        | 0x3041..=0x33FF
+        | 0x3400..=0x4DBF
+        | 0x4E00..=0x9FFF
+        | 0xA000..=0xA4CF
+        | 0xAC00..=0xD7A3
+        | 0xF900..=0xFAFF
+        | 0xFE30..=0xFE4F
+        | 0xFF00..=0xFF60
+        | 0xFFE0..=0xFFE6
+        | 0x1F300..=0x1F64F
+        | 0x1F900..=0x1F9FF
+        | 0x20000..=0x3FFFD => 2,
+        _ => 1,
+    }
+}
+
+// Cells text takes in a terminal
+pub fn text_width(text: &str) -> usize {
+    text.chars().map(char_width).sum()
+}
+
+// Keeps the end of text, fitting in width cells
+pub fn truncate_start(text: &str, width: usize) -> String {
+    if text_width(text) <= width || width < 2 {
+        return text.to_string();
+    }
+    // the ellipsis takes a cell
+    let mut tail = Vec::new();
+    let mut used = 1;
+    for c in text.chars().rev() {
+        if used + char_width(c) > width {
+            break;
+        }
+        used += char_width(c);
+        tail.push(c);
+    }
+    // a wide char cut in half is replaced by a space
+    let pad = " ".repeat(width - used);
+    format!("\u{2026}{}{}", pad, tail.iter().rev().collect::<String>())
+}
+
+// Pads or truncates text to exactly width cells
+pub fn align_text(text: &str, width: usize, align: Align) -> String {
+    let len = text_width(text);
+    if len >= width {
+        let mut truncated = String::new();
+        let mut used = 0;
+        for c in text.chars() {
+            if used + char_width(c) > width {
+                break;
+            }
+            used += char_width(c);
+            truncated.push(c);
+        }
+        // a wide char cut in half is replaced by a space
+        truncated.push_str(&" ".repeat(width - used));
+        return truncated;
+    }
+
+    let space = width - len;
+    let before = match align {
+        Align::Left => 0,
+        Align::Center => space / 2,
+        Align::Right => space,
+    };
+    format!(
+        "{}{}{}",
+        " ".repeat(before),
+        text,
+        " ".repeat(space - before)
+    )
+}
+
+impl Component<TUINode> for Label {
+    fn render(&self, _: Rc<BoxedState>, _: Rc<SetState>) -> El<TUINode> {
+        let props = &self.props;
+        let text = match props.width {
+            None => props.text.clone(),
+            Some(width) => align_text(&props.text, width.into(), props.align),
+        };
+
+        El::Node(Node::new(
+            TUINode::new(props.pos.left, props.pos.top)
+                .set_dimension(text_width(&text) as u16, 1)
+                .set_text(Some(text))
+                .disable(props.disabled),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn widths_of_chars() {
+        assert_eq!(text_width("abc"), 3);
+        assert_eq!(text_width("日本"), 4);
+        assert_eq!(text_width("e\u{301}"), 1);
+        assert_eq!(text_width("🎉!"), 3);
+    }
+
+    #[test]
+    fn align_text_pads_to_cells() {
+        assert_eq!(align_text("ab", 5, Align::Left), "ab   ");
+        assert_eq!(align_text("ab", 5, Align::Center), " ab  ");
+        assert_eq!(align_text("ab", 5, Align::Right), "   ab");
+        assert_eq!(align_text("日本", 6, Align::Center), " 日本 ");
+        assert_eq!(align_text("日本", 4, Align::Left), "日本");
+    }
+
+    #[test]
+    fn align_text_truncates_to_cells() {
+        assert_eq!(align_text("abcdef", 3, Align::Left), "abc");
+        assert_eq!(align_text("日本語", 4, Align::Left), "日本");
+        // the half of a wide char left is blank
+        assert_eq!(align_text("日本語", 5, Align::Right), "日本 ");
+        assert_eq!(align_text("日本", 1, Align::Left), " ");
+        assert_eq!(align_text("abc", 0, Align::Left), "");
+    }
+
+    #[test]
+    fn truncate_start_keeps_the_end() {
+        assert_eq!(truncate_start("abc", 3), "abc");
+        assert_eq!(truncate_start("abcdef", 4), "\u{2026}def");
+        assert_eq!(truncate_start("abcdef", 1), "abcdef");
+        assert_eq!(truncate_start("日本語", 5), "\u{2026}本語");
+        assert_eq!(truncate_start("日本語", 4), "\u{2026} 語");
+        assert_eq!(text_width(&truncate_start("a/日本語/b", 6)), 6);
+    }
+}
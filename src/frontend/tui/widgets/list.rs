@@ -0,0 +1,424 @@
+use std::any::Any;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use termion::event::Key;
+
+use crate::engine::*;
+use crate::frontend::tui::widgets::{align_text, move_cursor, Align, ChangeHandler};
+use crate::frontend::tui::*;
+
+// Idle delay after which a new key starts a new type-ahead search
+const TYPE_AHEAD_RESET: Duration = Duration::from_millis(1000);
+
+pub struct ListProps {
+    pub pos: Position,
+    pub dim: Dimension,
+    // shared with the handlers of the rendered list
+    pub items: Rc<Vec<String>>,
+    // Space and click toggle items instead of selecting a single one
+    pub multi_select: bool,
+    // called with the selected indices, in ascending order
+    pub on_select: Option<ChangeHandler<Vec<usize>>>,
+    pub disabled: bool,
+}
+
+impl Default for ListProps {
+    fn default() -> Self {
+        ListProps {
+            pos: Position {
+                ..Default::default()
+            },
+            dim: Dimension {
+                ..Default::default()
+            },
+            items: Rc::new(Vec::new()),
+            multi_select: false,
+            on_select: None,
+            disabled: false,
+        }
+    }
+}
+
+// Selectable list of items, navigated with the keyboard once focused by a click.
+// Only the visible rows are rendered to nodes.
+pub struct List {
+    pub props: ListProps,
+}
+
+#[derive(Clone)]
+struct ListState {
+    cursor: usize,
+    // index of the first visible row
+    offset: usize,
+    selected: Vec<usize>,
+    search: String,
+    last_search: Option<Instant>,
+}
+
+pub fn list(props: ListProps) -> El<TUINode> {
+    El::Component(Box::new(List { props }))
+}
+
+impl StateReceiver<ListState> for List {}
+impl Component<TUINode> for List {
+    fn initial_state(&self) -> Rc<dyn Any> {
+        Rc::new(ListState {
+            cursor: 0,
+            offset: 0,
+            selected: Vec::new(),
+            search: String::new(),
+            last_search: None,
+        })
+    }
+
+    fn render(&self, state: Rc<BoxedState>, set_state: Rc<SetState>) -> El<TUINode> {
+        let props = &self.props;
+        let height = usize::from(props.dim.height);
+        // items may have changed since the state was stored
+        let state = self
+            .must_receive_state(state)
+            .moved(0, props.items.len(), height);
+        let ctx = Rc::new(ListContext {
+            state: state.clone(),
+            items: props.items.clone(),
+            len: props.items.len(),
+            height,
+            multi_select: props.multi_select,
+            set_state,
+            on_select: props.on_select.clone(),
+        });
+
+        let rows = props
+            .items
+            .iter()
+            .enumerate()
+            .skip(state.offset)
+            .take(height)
+            .map(|(i, item)| {
+                let mark = match (props.multi_select, state.selected.contains(&i)) {
+                    (true, true) => "[x] ",
+                    (true, false) => "[ ] ",
+                    (false, true) => "* ",
+                    (false, false) => "  ",
+                };
+                El::Node(Node::new(
+                    TUINode::new(props.pos.left, props.pos.top + (i - state.offset) as u16)
+                        .set_dimension(props.dim.width, 1)
+                        .set_text(Some(align_text(
+                            &format!("{}{}", mark, item),
+                            props.dim.width.into(),
+                            Align::Left,
+                        )))
+                        .set_inverse(i == state.cursor)
+                        .set_on_click(Some(handle_on_click(ctx.clone(), i))),
+                ))
+            })
+            .collect();
+
+        El::Node(
+            Node::new(
+                TUINode::new(props.pos.left, props.pos.top)
+                    .set_dimension(props.dim.width, props.dim.height)
+                    .set_clip(true)
+                    .set_focusable(true)
+                    .disable(props.disabled)
+                    .set_on_key(Some(handle_on_key(ctx.clone())))
+                    .set_on_wheel(Some(handle_on_wheel(ctx))),
+            )
+            .add_children(rows),
+        )
+    }
+}
+
+// What the handlers of a rendered list need
+struct ListContext {
+    state: ListState,
+    items: Rc<Vec<String>>,
+    len: usize,
+    height: usize,
+    multi_select: bool,
+    set_state: Rc<SetState>,
+    on_select: Option<ChangeHandler<Vec<usize>>>,
+}
+
+impl ListContext {
+    fn commit(&self, state: ListState) {
+        (self.set_state)(Rc::new(state));
+    }
+
+    // Selects or toggles the item under the cursor, and notifies the selection
+    fn select(&self, mut state: ListState) -> Option<Message> {
+        let cursor = state.cursor;
+        if self.multi_select {
+            match state.selected.iter().position(|&i| i == cursor) {
+                Some(pos) => {
+                    state.selected.remove(pos);
+                }
+                None => {
+                    state.selected.push(cursor);
+                    state.selected.sort_unstable();
+                }
+            }
+        } else {
+            state.selected = vec![cursor];
+        }
+
+        let selected = state.selected.clone();
+        self.commit(state);
+        self.on_select.as_ref().and_then(|h| h(selected))
+    }
+}
+
+impl ListState {
+    fn moved(&self, delta: isize, len: usize, height: usize) -> ListState {
+        let (cursor, offset) = move_cursor(self.cursor, self.offset, delta, len, height);
+        ListState {
+            cursor,
+            offset,
+            ..self.clone()
+        }
+    }
+}
+
+fn handle_on_click(ctx: Rc<ListContext>, index: usize) -> MouseHandler {
+    Rc::new(move |_: &mut UIEvent| {
+        let mut state = ctx.state.clone();
+        state.cursor = index;
+        ctx.select(state)
+    })
+}
+
+fn handle_on_wheel(ctx: Rc<ListContext>) -> MouseHandler {
+    Rc::new(move |event: &mut UIEvent| {
+        let delta = match event.action {
+            MouseAction::Wheel(WheelDirection::Up) => -1,
+            MouseAction::Wheel(WheelDirection::Down) => 1,
+            _ => return None,
+        };
+        event.stop_propagation();
+        ctx.commit(ctx.state.moved(delta, ctx.len, ctx.height));
+        None
+    })
+}
+
+fn handle_on_key(ctx: Rc<ListContext>) -> KeyHandler {
+    Rc::new(move |event: &mut KeyEvent| {
+        let page = ctx.height.max(1) as isize;
+        let last = ctx.len as isize;
+        let delta = match event.key {
+            Key::Up => -1,
+            Key::Down => 1,
+            Key::PageUp => -page,
+            Key::PageDown => page,
+            Key::Home => -last,
+            Key::End => last,
+            Key::Char('\n') => {
+                event.stop_propagation();
+                return ctx.select(ctx.state.clone());
+            }
+            Key::Char(' ') if ctx.multi_select => {
+                event.stop_propagation();
+                return ctx.select(ctx.state.clone());
+            }
+            // keys matching no item bubble, e.g. to the quit and devtools keys
+            Key::Char(c) if !c.is_control() => {
+                if let Some(state) = type_ahead(&ctx, c) {
+                    event.stop_propagation();
+                    ctx.commit(state);
+                }
+                return None;
+            }
+            _ => return None,
+        };
+
+        event.stop_propagation();
+        let mut state = ctx.state.moved(delta, ctx.len, ctx.height);
+        state.search.clear();
+        ctx.commit(state);
+        None
+    })
+}
+
+// Moves the cursor to the next item starting with the typed search, case
+// insensitive. None: no item starts with it.
+fn type_ahead(ctx: &ListContext, c: char) -> Option<ListState> {
+    let mut state = ctx.state.clone();
+    let now = Instant::now();
+    match state.last_search {
+        Some(at) if now.duration_since(at) < TYPE_AHEAD_RESET => {}
+        _ => state.search.clear(),
+    }
+    state.search.extend(c.to_lowercase());
+    state.last_search = Some(now);
+
+    // a longer search may still match the current item
+    let start = if state.search.chars().count() > 1 {
+        state.cursor
+    } else {
+        state.cursor + 1
+    };
+    (0..ctx.len)
+        .map(|i| (start + i) % ctx.len)
+        .find(|&i| ctx.items[i].to_lowercase().starts_with(&state.search))
+        .map(|i| {
+            let delta = i as isize - state.cursor as isize;
+            let mut moved = state.moved(delta, ctx.len, ctx.height);
+            moved.search = state.search.clone();
+            moved
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::tui::widgets::testing::Harness;
+
+    fn harness() -> Harness {
+        harness_with(4, false)
+    }
+
+    fn harness_with(height: u16, multi_select: bool) -> Harness {
+        let items = Rc::new(
+            ["apple", "banana", "blueberry", "cherry"]
+                .iter()
+                .map(|s| s.to_string())
+                .collect::<Vec<_>>(),
+        );
+        let mut h = Harness::new(move || {
+            list(ListProps {
+                pos: Position { left: 1, top: 1 },
+                dim: Dimension { width: 12, height },
+                items: items.clone(),
+                multi_select,
+                on_select: Some(Rc::new(|selected: Vec<usize>| message(selected))),
+                ..Default::default()
+            })
+        });
+        // focused by a click, which selects the first item
+        h.click(1, 1);
+        h
+    }
+
+    // Row under the cursor, drawn inversed
+    fn cursor(h: &Harness) -> Option<String> {
+        fn find(e: &RenderedEl<TUINode>) -> Option<String> {
+            match e {
+                RenderedEl::None => None,
+                RenderedEl::Container(cont) => cont.iter().find_map(find),
+                RenderedEl::Node(node) if node.payload.style.inverse => node.payload.text.clone(),
+                RenderedEl::Node(node) => node.children.iter().find_map(find),
+            }
+        }
+        find(h.graph.as_ref()?).map(|t| t.trim().to_string())
+    }
+
+    #[test]
+    fn type_ahead_moves_to_matching_items() {
+        let mut h = harness();
+        assert_eq!(cursor(&h), Some("* apple".to_string()));
+        assert!(!h.press(Key::Char('b')));
+        assert_eq!(cursor(&h), Some("banana".to_string()));
+        assert!(!h.press(Key::Char('l')));
+        assert_eq!(cursor(&h), Some("blueberry".to_string()));
+    }
+
+    #[test]
+    fn keys_matching_no_item_bubble() {
+        let mut h = harness();
+        h.press(Key::Char('c'));
+        assert_eq!(cursor(&h), Some("cherry".to_string()));
+        // q quits once no handler consumes it
+        assert!(h.press(Key::Char('q')));
+        assert_eq!(cursor(&h), Some("cherry".to_string()));
+    }
+
+    fn rows(h: &Harness) -> Vec<String> {
+        h.texts().iter().map(|t| t.trim_end().to_string()).collect()
+    }
+
+    #[test]
+    fn only_the_visible_rows_are_rendered() {
+        let h = harness_with(2, false);
+        match &h.graph {
+            Some(RenderedEl::Node(list)) => assert_eq!(list.children.len(), 2),
+            _ => panic!("no list"),
+        }
+        assert_eq!(rows(&h), ["* apple", "  banana"]);
+    }
+
+    #[test]
+    fn keys_move_the_cursor_in_view() {
+        let mut h = harness_with(2, false);
+        let mut press = |key: Key| {
+            h.press(key);
+            (cursor(&h).unwrap(), rows(&h))
+        };
+        let row = |cursor: &str, rows: [&str; 2]| {
+            (
+                cursor.to_string(),
+                rows.iter().map(|r| r.to_string()).collect::<Vec<_>>(),
+            )
+        };
+        assert_eq!(press(Key::Down), row("banana", ["* apple", "  banana"]));
+        assert_eq!(
+            press(Key::Down),
+            row("blueberry", ["  banana", "  blueberry"])
+        );
+        assert_eq!(press(Key::End), row("cherry", ["  blueberry", "  cherry"]));
+        assert_eq!(press(Key::Down), row("cherry", ["  blueberry", "  cherry"]));
+        assert_eq!(
+            press(Key::Up),
+            row("blueberry", ["  blueberry", "  cherry"])
+        );
+        assert_eq!(press(Key::Home), row("* apple", ["* apple", "  banana"]));
+        assert_eq!(press(Key::Up), row("* apple", ["* apple", "  banana"]));
+        assert_eq!(
+            press(Key::PageDown),
+            row("blueberry", ["  banana", "  blueberry"])
+        );
+        assert_eq!(
+            press(Key::PageDown),
+            row("cherry", ["  blueberry", "  cherry"])
+        );
+        assert_eq!(
+            press(Key::PageUp),
+            row("banana", ["  banana", "  blueberry"])
+        );
+    }
+
+    #[test]
+    fn enter_selects_a_single_item() {
+        let mut h = harness_with(4, false);
+        assert_eq!(h.input.take_messages::<Vec<usize>>(), [vec![0]]);
+        h.press(Key::Down);
+        h.press(Key::Char('\n'));
+        assert_eq!(h.input.take_messages::<Vec<usize>>(), [vec![1]]);
+        assert_eq!(rows(&h)[..2], ["  apple", "* banana"]);
+    }
+
+    #[test]
+    fn multi_select_toggles_items() {
+        let mut h = harness_with(4, true);
+        // the focusing click toggled the first item
+        assert_eq!(h.input.take_messages::<Vec<usize>>(), [vec![0]]);
+
+        h.press(Key::Down);
+        h.press(Key::Down);
+        h.press(Key::Char(' '));
+        assert_eq!(h.input.take_messages::<Vec<usize>>(), [vec![0, 2]]);
+        h.click(1, 2);
+        assert_eq!(h.input.take_messages::<Vec<usize>>(), [vec![0, 1, 2]]);
+        h.click(1, 1);
+        assert_eq!(h.input.take_messages::<Vec<usize>>(), [vec![1, 2]]);
+        // the click moved the cursor
+        h.press(Key::Down);
+        h.press(Key::Char(' '));
+        assert_eq!(h.input.take_messages::<Vec<usize>>(), [vec![2]]);
+        // truncated to the width of the list
+        assert_eq!(
+            rows(&h),
+            ["[ ] apple", "[ ] banana", "[x] blueberr", "[ ] cherry"]
+        );
+    }
+}
@@ -0,0 +1,147 @@
+use std::rc::Rc;
+
+use crate::frontend::tui::Message;
+
+pub mod button;
+pub mod chart;
+pub mod checkbox;
+pub mod label;
+pub mod list;
+pub mod progress_bar;
+pub mod radio_group;
+pub mod separator;
+pub mod spinner;
+pub mod table;
+pub mod toggle;
+pub mod tree_view;
+
+pub use button::*;
+pub use chart::*;
+pub use checkbox::*;
+pub use label::*;
+pub use list::*;
+pub use progress_bar::*;
+pub use radio_group::*;
+pub use separator::*;
+pub use spinner::*;
+pub use table::*;
+pub use toggle::*;
+pub use tree_view::*;
+
+// Called with the new value when an input widget is changed by the user
+pub type ChangeHandler<T> = Rc<dyn Fn(T) -> Option<Message>>;
+
+// Moves a cursor by delta rows within len rows, and scrolls the offset of the
+// first visible row so that the cursor stays within the height visible rows.
+// Returns (cursor, offset).
+pub(crate) fn move_cursor(
+    cursor: usize,
+    offset: usize,
+    delta: isize,
+    len: usize,
+    height: usize,
+) -> (usize, usize) {
+    if len == 0 {
+        return (0, 0);
+    }
+
+    let cursor = (cursor as isize + delta).clamp(0, len as isize - 1) as usize;
+    let offset = if cursor < offset {
+        cursor
+    } else if height > 0 && cursor >= offset + height {
+        cursor + 1 - height
+    } else {
+        offset
+    };
+    (cursor, offset.min(len.saturating_sub(height)))
+}
+
+// Renders widgets and sends them mouse events, for the tests of the widgets
+#[cfg(test)]
+pub(crate) mod testing {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use termion::event::{Event, Key, MouseButton, MouseEvent};
+
+    use crate::engine::*;
+    use crate::frontend::tui::*;
+
+    pub struct Harness {
+        pub store: Rc<RefCell<StateStore>>,
+        pub input: InputState,
+        pub graph: Option<RenderedEl<TUINode>>,
+        app: Box<dyn Fn() -> El<TUINode>>,
+    }
+
+    impl Harness {
+        pub fn new(app: impl Fn() -> El<TUINode> + 'static) -> Self {
+            let mut harness = Harness {
+                store: new_state_store(),
+                input: InputState::new(),
+                graph: None,
+                app: Box::new(app),
+            };
+            harness.render();
+            harness
+        }
+
+        pub fn render(&mut self) {
+            self.graph = Some(render_app_to_graph(&(self.app)(), self.store.clone()));
+        }
+
+        // Clicks a cell, then renders the changed states
+        pub fn click(&mut self, left: u16, top: u16) {
+            let events = vec![
+                Ok(Event::Mouse(MouseEvent::Press(
+                    MouseButton::Left,
+                    left,
+                    top,
+                ))),
+                Ok(Event::Mouse(MouseEvent::Release(left, top))),
+            ];
+            process_events(&mut events.into_iter(), &self.graph, &mut self.input);
+            self.render();
+        }
+
+        // Turns the mouse wheel over a cell, then renders the changed states
+        pub fn wheel(&mut self, left: u16, top: u16, direction: WheelDirection) {
+            let button = match direction {
+                WheelDirection::Up => MouseButton::WheelUp,
+                WheelDirection::Down => MouseButton::WheelDown,
+            };
+            let events = vec![Ok(Event::Mouse(MouseEvent::Press(button, left, top)))];
+            process_events(&mut events.into_iter(), &self.graph, &mut self.input);
+            self.render();
+        }
+
+        // Presses a key, then renders the changed states. Returns whether the
+        // key quits the app.
+        pub fn press(&mut self, key: Key) -> bool {
+            let events = vec![Ok(Event::Key(key))];
+            let quit = process_events(&mut events.into_iter(), &self.graph, &mut self.input);
+            self.render();
+            quit
+        }
+
+        // Texts of the nodes, in tree order
+        pub fn texts(&self) -> Vec<String> {
+            let mut texts = Vec::new();
+            if let Some(graph) = &self.graph {
+                collect_texts(graph, &mut texts);
+            }
+            texts
+        }
+    }
+
+    fn collect_texts(e: &RenderedEl<TUINode>, texts: &mut Vec<String>) {
+        match e {
+            RenderedEl::None => {}
+            RenderedEl::Container(cont) => cont.iter().for_each(|ch| collect_texts(ch, texts)),
+            RenderedEl::Node(node) => {
+                texts.extend(node.payload.text.clone());
+                node.children.iter().for_each(|ch| collect_texts(ch, texts));
+            }
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use std::rc::Rc;
+
+use crate::engine::*;
+use crate::frontend::tui::*;
+
+// Partially filled cells, by eighths
+const PARTIAL_BLOCKS: [&str; 7] = ["▏", "▎", "▍", "▌", "▋", "▊", "▉"];
+
+pub struct ProgressBarProps {
+    pub pos: Position,
+    // width of the bar, border excluded
+    pub width: u16,
+    // clamped to 0..=100, NaN is 0
+    pub percent: f64,
+    pub border: bool,
+    // percentage displayed after the bar
+    pub label: bool,
+}
+
+impl Default for ProgressBarProps {
+    fn default() -> Self {
+        ProgressBarProps {
+            pos: Position {
+                ..Default::default()
+            },
+            width: 20,
+            percent: 0.0,
+            border: false,
+            label: true,
+        }
+    }
+}
+
+pub struct ProgressBar {
+    pub props: ProgressBarProps,
+}
+
+pub fn progress_bar(props: ProgressBarProps) -> El<TUINode> {
+    El::Component(Box::new(ProgressBar { props }))
+}
+
+// Bar of exactly width cells, filled up to percent with an eighth of cell precision
+pub fn filled_bar(width: u16, percent: f64) -> String {
+    let width = usize::from(width);
+    let eighths = (width as f64 * 8.0 * percent.clamp(0.0, 100.0) / 100.0).round() as usize;
+    let full = eighths / 8;
+    let partial = eighths % 8;
+
+    let mut bar = "█".repeat(full);
+    if partial > 0 {
+        bar.push_str(PARTIAL_BLOCKS[partial - 1]);
+    }
+    let filled = full + if partial > 0 { 1 } else { 0 };
+    bar.push_str(&" ".repeat(width - filled));
+    bar
+}
+
+impl Component<TUINode> for ProgressBar {
+    fn render(&self, _: Rc<BoxedState>, _: Rc<SetState>) -> El<TUINode> {
+        let props = &self.props;
+        // NaN shows as an empty bar
+        let percent = match props.percent {
+            p if p.is_nan() => 0.0,
+            p => p.clamp(0.0, 100.0),
+        };
+        let bar = filled_bar(props.width, percent);
+        let label_left = props.pos.left + props.width + if props.border { 3 } else { 1 };
+        let label_top = props.pos.top + if props.border { 1 } else { 0 };
+
+        El::Container(vec![
+            if props.border {
+                El::Node(Node::new(
+                    TUINode::new(props.pos.left, props.pos.top)
+                        .set_border(true)
+                        .set_dimension(props.width + 2, 3)
+                        .set_text(Some(bar)),
+                ))
+            } else {
+                El::Node(Node::new(
+                    TUINode::new(props.pos.left, props.pos.top)
+                        .set_dimension(props.width, 1)
+                        .set_text(Some(bar)),
+                ))
+            },
+            if props.label {
+                El::Node(Node::new(
+                    TUINode::new(label_left, label_top).set_text(Some(format!("{:.0} %", percent))),
+                ))
+            } else {
+                El::None
+            },
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::tui::widgets::testing::Harness;
+    use crate::frontend::tui::widgets::text_width;
+
+    #[test]
+    fn empty_bar() {
+        assert_eq!(filled_bar(4, 0.0), "    ");
+        assert_eq!(filled_bar(0, 50.0), "");
+    }
+
+    #[test]
+    fn partial_bar() {
+        assert_eq!(filled_bar(4, 50.0), "██  ");
+        // 1 cell and 3 eighths
+        assert_eq!(filled_bar(4, 34.375), "█▍  ");
+    }
+
+    #[test]
+    fn full_bar() {
+        assert_eq!(filled_bar(4, 100.0), "████");
+    }
+
+    #[test]
+    fn out_of_range_percents_are_clamped() {
+        assert_eq!(filled_bar(4, 250.0), "████");
+        assert_eq!(filled_bar(4, -10.0), "    ");
+        assert_eq!(filled_bar(4, f64::NAN), "    ");
+        assert_eq!(filled_bar(4, f64::INFINITY), "████");
+        for percent in [f64::NAN, -1.0, 0.1, 99.9, 1e9] {
+            assert_eq!(text_width(&filled_bar(7, percent)), 7);
+        }
+    }
+
+    #[test]
+    fn labels_show_the_clamped_percent() {
+        for (percent, label) in [(42.4, "42 %"), (130.0, "100 %"), (f64::NAN, "0 %")] {
+            let h = Harness::new(move || {
+                progress_bar(ProgressBarProps {
+                    width: 4,
+                    percent,
+                    ..Default::default()
+                })
+            });
+            assert_eq!(h.texts()[1], label);
+        }
+    }
+}
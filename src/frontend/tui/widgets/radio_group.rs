@@ -0,0 +1,136 @@
+use std::any::Any;
+use std::rc::Rc;
+
+use crate::engine::*;
+use crate::frontend::tui::widgets::{text_width, ChangeHandler};
+use crate::frontend::tui::*;
+
+// Options stacked vertically, one line each, exactly one selected
+pub struct RadioGroupProps {
+    pub pos: Position,
+    pub options: Vec<String>,
+    // initial selection; the current one is kept in the component state
+    pub selected: usize,
+    // called with the index of the selected option
+    pub on_change: Option<ChangeHandler<usize>>,
+    pub disabled: bool,
+}
+
+impl Default for RadioGroupProps {
+    fn default() -> Self {
+        RadioGroupProps {
+            pos: Position {
+                ..Default::default()
+            },
+            options: Vec::new(),
+            selected: 0,
+            on_change: None,
+            disabled: false,
+        }
+    }
+}
+
+pub struct RadioGroup {
+    pub props: RadioGroupProps,
+}
+
+#[derive(Clone)]
+struct RadioGroupState {
+    selected: usize,
+}
+
+pub fn radio_group(props: RadioGroupProps) -> El<TUINode> {
+    El::Component(Box::new(RadioGroup { props }))
+}
+
+impl StateReceiver<RadioGroupState> for RadioGroup {}
+impl Component<TUINode> for RadioGroup {
+    fn initial_state(&self) -> Rc<dyn Any> {
+        Rc::new(RadioGroupState {
+            selected: self.props.selected,
+        })
+    }
+
+    fn render(&self, state: Rc<BoxedState>, set_state: Rc<SetState>) -> El<TUINode> {
+        let state = self.must_receive_state(state);
+        let props = &self.props;
+
+        El::Container(
+            props
+                .options
+                .iter()
+                .enumerate()
+                .map(|(i, option)| {
+                    let text = format!(
+                        "({}) {}",
+                        if i == state.selected { "•" } else { " " },
+                        option
+                    );
+                    El::Node(Node::new(
+                        TUINode::new(props.pos.left, props.pos.top + i as u16)
+                            .set_dimension(text_width(&text) as u16, 1)
+                            .set_text(Some(text))
+                            .disable(props.disabled)
+                            .set_on_click(Some(handle_on_click(
+                                i,
+                                set_state.clone(),
+                                props.on_change.clone(),
+                            ))),
+                    ))
+                })
+                .collect(),
+        )
+    }
+}
+
+fn handle_on_click(
+    selected: usize,
+    set_state: Rc<SetState>,
+    on_change: Option<ChangeHandler<usize>>,
+) -> MouseHandler {
+    Rc::new(move |_: &mut UIEvent| {
+        set_state(Rc::new(RadioGroupState { selected }));
+        on_change.as_ref().and_then(|h| h(selected))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::tui::widgets::testing::Harness;
+
+    fn harness(disabled: bool) -> Harness {
+        Harness::new(move || {
+            radio_group(RadioGroupProps {
+                pos: Position { left: 1, top: 1 },
+                options: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+                selected: 1,
+                on_change: Some(Rc::new(|i| Some(Box::new(i)))),
+                disabled,
+            })
+        })
+    }
+
+    #[test]
+    fn clicks_select_an_option() {
+        let mut h = harness(false);
+        assert_eq!(h.texts(), ["( ) a", "(•) b", "( ) c"]);
+        h.click(2, 3);
+        assert_eq!(h.texts(), ["( ) a", "( ) b", "(•) c"]);
+        assert_eq!(h.input.take_messages::<usize>(), [2]);
+        // selected again
+        h.click(5, 3);
+        assert_eq!(h.texts(), ["( ) a", "( ) b", "(•) c"]);
+        assert_eq!(h.input.take_messages::<usize>(), [2]);
+        h.click(1, 1);
+        assert_eq!(h.texts(), ["(•) a", "( ) b", "( ) c"]);
+        assert_eq!(h.input.take_messages::<usize>(), [0]);
+    }
+
+    #[test]
+    fn disabled_options_are_not_selected() {
+        let mut h = harness(true);
+        h.click(1, 1);
+        assert_eq!(h.texts(), ["( ) a", "(•) b", "( ) c"]);
+    }
+}
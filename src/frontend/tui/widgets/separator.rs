@@ -0,0 +1,87 @@
+use std::rc::Rc;
+
+use crate::engine::*;
+use crate::frontend::tui::*;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Orientation {
+    Horizontal,
+    Vertical,
+}
+
+pub struct SeparatorProps {
+    pub pos: Position,
+    pub length: u16,
+    pub orientation: Orientation,
+}
+
+impl Default for SeparatorProps {
+    fn default() -> Self {
+        SeparatorProps {
+            pos: Position {
+                ..Default::default()
+            },
+            length: 0,
+            orientation: Orientation::Horizontal,
+        }
+    }
+}
+
+pub struct Separator {
+    pub props: SeparatorProps,
+}
+
+pub fn separator(props: SeparatorProps) -> El<TUINode> {
+    El::Component(Box::new(Separator { props }))
+}
+
+impl Component<TUINode> for Separator {
+    fn render(&self, _: Rc<BoxedState>, _: Rc<SetState>) -> El<TUINode> {
+        let props = &self.props;
+        match props.orientation {
+            Orientation::Horizontal => El::Node(Node::new(
+                TUINode::new(props.pos.left, props.pos.top)
+                    .set_dimension(props.length, 1)
+                    .set_text(Some("─".repeat(props.length.into()))),
+            )),
+            // nodes hold a single line of text
+            Orientation::Vertical => El::Container(
+                (0..props.length)
+                    .map(|i| {
+                        El::Node(Node::new(
+                            TUINode::new(props.pos.left, props.pos.top + i)
+                                .set_text(Some("│".to_string())),
+                        ))
+                    })
+                    .collect(),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::tui::widgets::testing::Harness;
+
+    fn texts(orientation: Orientation) -> Vec<String> {
+        Harness::new(move || {
+            separator(SeparatorProps {
+                pos: Position { left: 1, top: 1 },
+                length: 3,
+                orientation,
+            })
+        })
+        .texts()
+    }
+
+    #[test]
+    fn horizontal_separators_are_one_line() {
+        assert_eq!(texts(Orientation::Horizontal), ["───"]);
+    }
+
+    #[test]
+    fn vertical_separators_are_one_node_per_line() {
+        assert_eq!(texts(Orientation::Vertical), ["│", "│", "│"]);
+    }
+}
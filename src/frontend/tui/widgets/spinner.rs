@@ -0,0 +1,98 @@
+use std::rc::Rc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::engine::*;
+use crate::frontend::tui::animation::FrameClock;
+use crate::frontend::tui::widgets::text_width;
+use crate::frontend::tui::*;
+
+const FRAMES: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+pub struct SpinnerProps {
+    pub pos: Position,
+    pub label: String,
+    // time each frame is displayed
+    pub interval: Duration,
+    // None: the wall clock; otherwise frames are requested while it spins
+    pub clock: Option<FrameClock>,
+}
+
+impl Default for SpinnerProps {
+    fn default() -> Self {
+        SpinnerProps {
+            pos: Position {
+                ..Default::default()
+            },
+            label: "".to_string(),
+            interval: Duration::from_millis(80),
+            clock: None,
+        }
+    }
+}
+
+// The frame depends on the clock: the spinner moves as long as the app re-renders
+pub struct Spinner {
+    pub props: SpinnerProps,
+}
+
+pub fn spinner(props: SpinnerProps) -> El<TUINode> {
+    El::Component(Box::new(Spinner { props }))
+}
+
+impl Component<TUINode> for Spinner {
+    fn render(&self, _: Rc<BoxedState>, _: Rc<SetState>) -> El<TUINode> {
+        let props = &self.props;
+        let elapsed = match &props.clock {
+            Some(clock) => {
+                clock.request_frame();
+                clock.elapsed()
+            }
+            None => SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default(),
+        }
+        .as_millis();
+        let frame = (elapsed / props.interval.as_millis().max(1)) as usize % FRAMES.len();
+
+        let text = if props.label.is_empty() {
+            FRAMES[frame].to_string()
+        } else {
+            format!("{} {}", FRAMES[frame], props.label)
+        };
+
+        El::Node(Node::new(
+            TUINode::new(props.pos.left, props.pos.top)
+                .set_dimension(text_width(&text) as u16, 1)
+                .set_text(Some(text)),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::tui::widgets::testing::Harness;
+
+    #[test]
+    fn frames_follow_the_clock() {
+        let clock = FrameClock::new();
+        let props_clock = clock.clone();
+        let mut h = Harness::new(move || {
+            spinner(SpinnerProps {
+                label: "Loading".to_string(),
+                clock: Some(props_clock.clone()),
+                ..Default::default()
+            })
+        });
+        assert_eq!(h.texts(), ["⠋ Loading"]);
+        assert!(clock.take_frame_request());
+
+        let origin = clock.now();
+        for (millis, frame) in [(79, "⠋"), (80, "⠙"), (250, "⠸"), (800, "⠋"), (1000, "⠹")]
+        {
+            clock.tick(origin + Duration::from_millis(millis));
+            h.render();
+            assert_eq!(h.texts(), [format!("{} Loading", frame)]);
+        }
+    }
+}
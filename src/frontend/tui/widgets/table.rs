@@ -0,0 +1,554 @@
+use std::any::Any;
+use std::cmp::Ordering;
+use std::rc::Rc;
+
+use termion::event::Key;
+
+use crate::engine::*;
+use crate::frontend::tui::widgets::{align_text, move_cursor, Align, ChangeHandler};
+use crate::frontend::tui::*;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColumnWidth {
+    Fixed(u16),
+    // of the table width, column gaps excluded
+    Percent(u16),
+    // shares the width left by the other columns with the other auto columns
+    Auto,
+}
+
+pub struct Column {
+    pub title: String,
+    pub width: ColumnWidth,
+    pub align: Align,
+    // sorted on click on its header
+    pub sortable: bool,
+}
+
+impl Default for Column {
+    fn default() -> Self {
+        Column {
+            title: "".to_string(),
+            width: ColumnWidth::Auto,
+            align: Align::Left,
+            sortable: true,
+        }
+    }
+}
+
+pub struct TableProps {
+    pub pos: Position,
+    // header and its underline included
+    pub dim: Dimension,
+    pub columns: Vec<Column>,
+    // shared with the handlers of the rendered table
+    pub rows: Rc<Vec<Vec<String>>>,
+    // called with the index in rows of the selected row
+    pub on_select: Option<ChangeHandler<usize>>,
+    pub disabled: bool,
+}
+
+impl Default for TableProps {
+    fn default() -> Self {
+        TableProps {
+            pos: Position {
+                ..Default::default()
+            },
+            dim: Dimension {
+                ..Default::default()
+            },
+            columns: Vec::new(),
+            rows: Rc::new(Vec::new()),
+            on_select: None,
+            disabled: false,
+        }
+    }
+}
+
+// Table with a header row, navigated with the keyboard once focused by a click.
+// Only the visible rows are rendered to nodes.
+pub struct Table {
+    pub props: TableProps,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+struct SortOrder {
+    column: usize,
+    ascending: bool,
+}
+
+#[derive(Clone)]
+struct TableState {
+    // index in rows of the row under the cursor
+    cursor_row: usize,
+    // index of the first visible row, in display order
+    offset: usize,
+    sort: Option<SortOrder>,
+}
+
+pub fn table(props: TableProps) -> El<TUINode> {
+    El::Component(Box::new(Table { props }))
+}
+
+// Widths of the columns laid out on width cells, one cell apart
+pub fn column_widths(columns: &[Column], width: u16) -> Vec<u16> {
+    let gaps = columns.len().saturating_sub(1) as u16;
+    let available = width.saturating_sub(gaps);
+
+    let mut widths: Vec<u16> = columns
+        .iter()
+        .map(|c| match c.width {
+            ColumnWidth::Fixed(w) => w,
+            ColumnWidth::Percent(p) => (u32::from(available) * u32::from(p) / 100) as u16,
+            ColumnWidth::Auto => 0,
+        })
+        .collect();
+
+    let autos = columns
+        .iter()
+        .filter(|c| c.width == ColumnWidth::Auto)
+        .count() as u16;
+    if autos > 0 {
+        let used = widths.iter().fold(0u16, |acc, w| acc.saturating_add(*w));
+        let remaining = available.saturating_sub(used);
+        let mut extra = remaining % autos;
+        for (w, c) in widths.iter_mut().zip(columns) {
+            if c.width == ColumnWidth::Auto {
+                *w = remaining / autos + if extra > 0 { 1 } else { 0 };
+                extra = extra.saturating_sub(1);
+            }
+        }
+    }
+
+    widths
+}
+
+// Key a column is sorted by: all the numbers, then all the text
+enum SortKey<'a> {
+    Num(f64),
+    Text(&'a str),
+}
+
+fn sort_key(cell: &str) -> SortKey<'_> {
+    match cell.trim().parse::<f64>() {
+        Ok(n) => SortKey::Num(n),
+        Err(_) => SortKey::Text(cell),
+    }
+}
+
+// A total order: numbers compare with total_cmp, which puts NaN after infinity
+fn compare_cells(a: &str, b: &str) -> Ordering {
+    match (sort_key(a), sort_key(b)) {
+        (SortKey::Num(x), SortKey::Num(y)) => x.total_cmp(&y),
+        (SortKey::Num(_), SortKey::Text(_)) => Ordering::Less,
+        (SortKey::Text(_), SortKey::Num(_)) => Ordering::Greater,
+        (SortKey::Text(x), SortKey::Text(y)) => x.cmp(y),
+    }
+}
+
+// Indices in rows, in display order
+fn display_order(rows: &[Vec<String>], sort: Option<SortOrder>) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..rows.len()).collect();
+    if let Some(sort) = sort {
+        let cell = |row: usize| rows[row].get(sort.column).map_or("", |c| c.as_str());
+        // stable: equal cells keep the order of rows
+        order.sort_by(|&a, &b| {
+            let ord = compare_cells(cell(a), cell(b));
+            if sort.ascending {
+                ord
+            } else {
+                ord.reverse()
+            }
+        });
+    }
+    order
+}
+
+impl StateReceiver<TableState> for Table {}
+impl Component<TUINode> for Table {
+    fn initial_state(&self) -> Rc<dyn Any> {
+        Rc::new(TableState {
+            cursor_row: 0,
+            offset: 0,
+            sort: None,
+        })
+    }
+
+    fn render(&self, state: Rc<BoxedState>, set_state: Rc<SetState>) -> El<TUINode> {
+        let props = &self.props;
+        let state = self.must_receive_state(state);
+        let widths = column_widths(&props.columns, props.dim.width);
+        // header and its underline
+        let height = usize::from(props.dim.height.saturating_sub(2));
+
+        let order = Rc::new(display_order(&props.rows, state.sort));
+        let cursor = order
+            .iter()
+            .position(|&r| r == state.cursor_row)
+            .unwrap_or(0);
+        // rows may have changed since the state was stored
+        let (cursor, offset) = move_cursor(cursor, state.offset, 0, order.len(), height);
+
+        let ctx = Rc::new(TableContext {
+            state: state.clone(),
+            order: order.clone(),
+            cursor,
+            offset,
+            height,
+            set_state,
+            on_select: props.on_select.clone(),
+        });
+
+        let rows_top = props.pos.top + 2;
+        let rows = order
+            .iter()
+            .enumerate()
+            .skip(offset)
+            .take(height)
+            .map(|(i, &row)| {
+                let cells = &props.rows[row];
+                let text = widths
+                    .iter()
+                    .zip(&props.columns)
+                    .enumerate()
+                    .map(|(col, (w, c))| {
+                        let cell = cells.get(col).map_or("", |c| c.as_str());
+                        align_text(cell, usize::from(*w), c.align)
+                    })
+                    .collect::<Vec<String>>()
+                    .join(" ");
+
+                El::Node(Node::new(
+                    TUINode::new(props.pos.left, rows_top + (i - offset) as u16)
+                        .set_dimension(props.dim.width, 1)
+                        .set_text(Some(text))
+                        .set_inverse(i == cursor)
+                        .set_on_click(Some(handle_on_row_click(ctx.clone(), i))),
+                ))
+            })
+            .collect();
+
+        El::Node(
+            Node::new(
+                TUINode::new(props.pos.left, props.pos.top)
+                    .set_dimension(props.dim.width, props.dim.height)
+                    .set_clip(true)
+                    .set_focusable(true)
+                    .disable(props.disabled)
+                    .set_on_key(Some(handle_on_key(ctx.clone())))
+                    .set_on_wheel(Some(handle_on_wheel(ctx.clone()))),
+            )
+            .add_children(self.render_header(&widths, &ctx))
+            .add_child(El::Node(Node::new(
+                TUINode::new(props.pos.left, props.pos.top + 1)
+                    .set_dimension(props.dim.width, 1)
+                    .set_text(Some("─".repeat(props.dim.width.into()))),
+            )))
+            .add_children(rows),
+        )
+    }
+}
+
+impl Table {
+    fn render_header(&self, widths: &[u16], ctx: &Rc<TableContext>) -> Vec<El<TUINode>> {
+        let props = &self.props;
+        let mut left = props.pos.left;
+
+        widths
+            .iter()
+            .zip(&props.columns)
+            .enumerate()
+            .map(|(col, (w, c))| {
+                let indicator = match ctx.state.sort {
+                    Some(s) if s.column == col && s.ascending => " ▲",
+                    Some(s) if s.column == col => " ▼",
+                    _ => "",
+                };
+                let node = TUINode::new(left, props.pos.top)
+                    .set_dimension(*w, 1)
+                    .set_text(Some(align_text(
+                        &format!("{}{}", c.title, indicator),
+                        usize::from(*w),
+                        c.align,
+                    )))
+                    .set_on_click(if c.sortable {
+                        Some(handle_on_header_click(ctx.clone(), col))
+                    } else {
+                        None
+                    });
+                left = left.saturating_add(w + 1);
+                El::Node(Node::new(node))
+            })
+            .collect()
+    }
+}
+
+// What the handlers of a rendered table need
+struct TableContext {
+    state: TableState,
+    order: Rc<Vec<usize>>,
+    // position of the cursor in display order
+    cursor: usize,
+    offset: usize,
+    height: usize,
+    set_state: Rc<SetState>,
+    on_select: Option<ChangeHandler<usize>>,
+}
+
+impl TableContext {
+    // Moves the cursor by delta rows, in display order
+    fn move_by(&self, delta: isize) -> TableState {
+        let (cursor, offset) = move_cursor(
+            self.cursor,
+            self.offset,
+            delta,
+            self.order.len(),
+            self.height,
+        );
+        TableState {
+            cursor_row: self.order.get(cursor).copied().unwrap_or(0),
+            offset,
+            ..self.state.clone()
+        }
+    }
+
+    fn commit(&self, state: TableState) {
+        (self.set_state)(Rc::new(state));
+    }
+
+    fn select(&self, state: TableState) -> Option<Message> {
+        let row = state.cursor_row;
+        self.commit(state);
+        match self.order.is_empty() {
+            true => None,
+            false => self.on_select.as_ref().and_then(|h| h(row)),
+        }
+    }
+}
+
+fn handle_on_row_click(ctx: Rc<TableContext>, position: usize) -> MouseHandler {
+    Rc::new(move |_: &mut UIEvent| ctx.select(ctx.move_by(position as isize - ctx.cursor as isize)))
+}
+
+fn handle_on_header_click(ctx: Rc<TableContext>, column: usize) -> MouseHandler {
+    Rc::new(move |_: &mut UIEvent| {
+        let ascending = match ctx.state.sort {
+            Some(s) if s.column == column => !s.ascending,
+            _ => true,
+        };
+        ctx.commit(TableState {
+            sort: Some(SortOrder { column, ascending }),
+            // the cursor follows its row to its sorted position
+            offset: 0,
+            ..ctx.state.clone()
+        });
+        None
+    })
+}
+
+fn handle_on_wheel(ctx: Rc<TableContext>) -> MouseHandler {
+    Rc::new(move |event: &mut UIEvent| {
+        let delta = match event.action {
+            MouseAction::Wheel(WheelDirection::Up) => -1,
+            MouseAction::Wheel(WheelDirection::Down) => 1,
+            _ => return None,
+        };
+        event.stop_propagation();
+        ctx.commit(ctx.move_by(delta));
+        None
+    })
+}
+
+fn handle_on_key(ctx: Rc<TableContext>) -> KeyHandler {
+    Rc::new(move |event: &mut KeyEvent| {
+        let page = ctx.height.max(1) as isize;
+        let last = ctx.order.len() as isize;
+        let delta = match event.key {
+            Key::Up => -1,
+            Key::Down => 1,
+            Key::PageUp => -page,
+            Key::PageDown => page,
+            Key::Home => -last,
+            Key::End => last,
+            Key::Char('\n') => {
+                event.stop_propagation();
+                return ctx.select(ctx.state.clone());
+            }
+            _ => return None,
+        };
+        event.stop_propagation();
+        ctx.commit(ctx.move_by(delta));
+        None
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::tui::widgets::testing::Harness;
+
+    fn column(cells: &[&str]) -> Vec<Vec<String>> {
+        cells.iter().map(|c| vec![c.to_string()]).collect()
+    }
+
+    fn sorted(rows: &[Vec<String>], ascending: bool) -> Vec<&str> {
+        display_order(
+            rows,
+            Some(SortOrder {
+                column: 0,
+                ascending,
+            }),
+        )
+        .into_iter()
+        .map(|r| rows[r][0].as_str())
+        .collect()
+    }
+
+    #[test]
+    fn numbers_sort_before_text() {
+        let rows = column(&["b", "10", "1a", "9", "a", "-2.5"]);
+        assert_eq!(sorted(&rows, true), ["-2.5", "9", "10", "1a", "a", "b"]);
+        assert_eq!(sorted(&rows, false), ["b", "a", "1a", "10", "9", "-2.5"]);
+    }
+
+    #[test]
+    fn nan_sorts_after_the_numbers() {
+        let rows = column(&["NaN", "3", "x", "inf", "1"]);
+        assert_eq!(sorted(&rows, true), ["1", "3", "inf", "NaN", "x"]);
+    }
+
+    #[test]
+    fn mixed_columns_sort_without_panicking() {
+        let values = [
+            "10", "9", "1a", "NaN", "", " 2 ", "-0", "0", "abc", "1e3", "inf", "Z",
+        ];
+        // xorshift, for reproducible columns
+        let mut seed: u32 = 0x9e37_79b9;
+        for _ in 0..200 {
+            let rows: Vec<Vec<String>> = (0..40)
+                .map(|_| {
+                    seed ^= seed << 13;
+                    seed ^= seed >> 17;
+                    seed ^= seed << 5;
+                    vec![values[seed as usize % values.len()].to_string()]
+                })
+                .collect();
+            let order = sorted(&rows, true);
+            assert!(order
+                .windows(2)
+                .all(|w| compare_cells(w[0], w[1]) != Ordering::Greater));
+        }
+    }
+
+    fn widths(columns: &[ColumnWidth], width: u16) -> Vec<u16> {
+        let columns: Vec<Column> = columns
+            .iter()
+            .map(|&width| Column {
+                width,
+                ..Default::default()
+            })
+            .collect();
+        column_widths(&columns, width)
+    }
+
+    #[test]
+    fn auto_columns_share_the_width_left() {
+        use ColumnWidth::*;
+        // 3 gaps
+        assert_eq!(
+            widths(&[Fixed(5), Percent(50), Auto, Auto], 33),
+            [5, 15, 5, 5]
+        );
+        // the first auto columns take the remainder
+        assert_eq!(
+            widths(&[Fixed(5), Percent(50), Auto, Auto], 34),
+            [5, 15, 6, 5]
+        );
+        assert_eq!(widths(&[Auto, Auto, Auto], 12), [4, 3, 3]);
+    }
+
+    #[test]
+    fn overflowing_columns_leave_nothing_to_auto_ones() {
+        use ColumnWidth::*;
+        assert_eq!(widths(&[Fixed(20), Auto], 10), [20, 0]);
+        assert_eq!(widths(&[Percent(100), Auto], 10), [9, 0]);
+        assert_eq!(widths(&[Auto], 0), [0]);
+    }
+
+    fn harness() -> Harness {
+        let rows = Rc::new(vec![
+            vec!["banana".to_string(), "12".to_string()],
+            vec!["apple".to_string(), "3".to_string()],
+            vec!["cherry pie".to_string(), "100".to_string()],
+        ]);
+        Harness::new(move || {
+            table(TableProps {
+                dim: Dimension {
+                    width: 12,
+                    height: 5,
+                },
+                columns: vec![
+                    Column {
+                        title: "Name".to_string(),
+                        ..Default::default()
+                    },
+                    Column {
+                        title: "Qty".to_string(),
+                        width: ColumnWidth::Fixed(5),
+                        align: Align::Right,
+                        ..Default::default()
+                    },
+                ],
+                rows: rows.clone(),
+                on_select: Some(Rc::new(|row: usize| message(row))),
+                ..Default::default()
+            })
+        })
+    }
+
+    // Rows under the header and its underline
+    fn rows(h: &Harness) -> Vec<String> {
+        h.texts()[3..].to_vec()
+    }
+
+    #[test]
+    fn cells_are_aligned_and_truncated() {
+        let h = harness();
+        assert_eq!(h.texts()[..3], ["Name  ", "  Qty", "────────────"]);
+        assert_eq!(rows(&h), ["banana    12", "apple      3", "cherry   100"]);
+    }
+
+    #[test]
+    fn header_clicks_sort_the_rows() {
+        let mut h = harness();
+        h.click(8, 0);
+        assert_eq!(h.texts()[1], "Qty ▲");
+        assert_eq!(rows(&h), ["apple      3", "banana    12", "cherry   100"]);
+        h.click(8, 0);
+        assert_eq!(h.texts()[1], "Qty ▼");
+        assert_eq!(rows(&h), ["cherry   100", "banana    12", "apple      3"]);
+        h.click(0, 0);
+        assert_eq!(h.texts()[..2], ["Name ▲", "  Qty"]);
+        assert_eq!(rows(&h), ["apple      3", "banana    12", "cherry   100"]);
+    }
+
+    #[test]
+    fn rows_are_selected_by_their_index_in_rows() {
+        let mut h = harness();
+        // sorted by quantity
+        h.click(8, 0);
+        h.click(0, 3);
+        assert_eq!(h.input.take_messages::<usize>(), [0]);
+        h.press(Key::Down);
+        h.press(Key::Char('\n'));
+        assert_eq!(h.input.take_messages::<usize>(), [2]);
+
+        // the cursor follows its row once sorted again
+        h.click(8, 0);
+        h.press(Key::Char('\n'));
+        assert_eq!(h.input.take_messages::<usize>(), [2]);
+        h.press(Key::Down);
+        h.press(Key::Char('\n'));
+        assert_eq!(h.input.take_messages::<usize>(), [0]);
+    }
+}
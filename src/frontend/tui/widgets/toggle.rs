@@ -0,0 +1,108 @@
+use std::any::Any;
+use std::rc::Rc;
+
+use crate::engine::*;
+use crate::frontend::tui::widgets::{text_width, ChangeHandler};
+use crate::frontend::tui::*;
+
+pub struct ToggleProps {
+    pub pos: Position,
+    pub label: String,
+    // initial value; the current one is kept in the component state
+    pub on: bool,
+    pub on_change: Option<ChangeHandler<bool>>,
+    pub disabled: bool,
+}
+
+impl Default for ToggleProps {
+    fn default() -> Self {
+        ToggleProps {
+            pos: Position {
+                ..Default::default()
+            },
+            label: "".to_string(),
+            on: false,
+            on_change: None,
+            disabled: false,
+        }
+    }
+}
+
+pub struct Toggle {
+    pub props: ToggleProps,
+}
+
+#[derive(Clone)]
+struct ToggleState {
+    on: bool,
+}
+
+pub fn toggle(props: ToggleProps) -> El<TUINode> {
+    El::Component(Box::new(Toggle { props }))
+}
+
+impl StateReceiver<ToggleState> for Toggle {}
+impl Component<TUINode> for Toggle {
+    fn initial_state(&self) -> Rc<dyn Any> {
+        Rc::new(ToggleState { on: self.props.on })
+    }
+
+    fn render(&self, state: Rc<BoxedState>, set_state: Rc<SetState>) -> El<TUINode> {
+        let state = self.must_receive_state(state);
+        let props = &self.props;
+        let text = format!(
+            "{} {}",
+            if state.on { "[ ON]" } else { "[OFF]" },
+            props.label
+        );
+
+        El::Node(Node::new(
+            TUINode::new(props.pos.left, props.pos.top)
+                .set_dimension(text_width(&text) as u16, 1)
+                .set_text(Some(text))
+                .disable(props.disabled)
+                .set_on_click(Some(handle_on_click(
+                    !state.on,
+                    set_state,
+                    props.on_change.clone(),
+                ))),
+        ))
+    }
+}
+
+fn handle_on_click(
+    on: bool,
+    set_state: Rc<SetState>,
+    on_change: Option<ChangeHandler<bool>>,
+) -> MouseHandler {
+    Rc::new(move |_: &mut UIEvent| {
+        set_state(Rc::new(ToggleState { on }));
+        on_change.as_ref().and_then(|h| h(on))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::tui::widgets::testing::Harness;
+
+    #[test]
+    fn clicks_switch_it() {
+        let mut h = Harness::new(|| {
+            toggle(ToggleProps {
+                pos: Position { left: 1, top: 1 },
+                label: "Sound".to_string(),
+                on: true,
+                on_change: Some(Rc::new(|on| Some(Box::new(on)))),
+                ..Default::default()
+            })
+        });
+        assert_eq!(h.texts(), ["[ ON] Sound"]);
+        h.click(1, 1);
+        assert_eq!(h.texts(), ["[OFF] Sound"]);
+        assert_eq!(h.input.take_messages::<bool>(), [false]);
+        h.click(11, 1);
+        assert_eq!(h.texts(), ["[ ON] Sound"]);
+        assert_eq!(h.input.take_messages::<bool>(), [true]);
+    }
+}
@@ -0,0 +1,527 @@
+use std::any::Any;
+use std::collections::HashSet;
+use std::rc::Rc;
+
+use termion::event::Key;
+
+use crate::engine::*;
+use crate::frontend::tui::widgets::{align_text, move_cursor, Align, ChangeHandler};
+use crate::frontend::tui::*;
+
+// Item of the tree, owning its children like a Node
+pub struct TreeItem {
+    // unique among all the items of the tree; keys the expanded state
+    pub id: String,
+    pub label: String,
+    pub children: Vec<TreeItem>,
+    // children are not known yet: on_load is called when it is expanded
+    pub lazy: bool,
+}
+
+impl TreeItem {
+    pub fn new(id: &str, label: &str) -> Self {
+        TreeItem {
+            id: id.to_string(),
+            label: label.to_string(),
+            children: Vec::new(),
+            lazy: false,
+        }
+    }
+
+    pub fn add_child(mut self, child: TreeItem) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    pub fn add_children(mut self, children: Vec<TreeItem>) -> Self {
+        self.children.extend(children);
+        self
+    }
+
+    pub fn set_lazy(mut self, lazy: bool) -> Self {
+        self.lazy = lazy;
+        self
+    }
+
+    fn expandable(&self) -> bool {
+        self.lazy || !self.children.is_empty()
+    }
+}
+
+pub struct TreeViewProps {
+    pub pos: Position,
+    pub dim: Dimension,
+    // top level items, shared with the handlers of the rendered tree
+    pub items: Rc<Vec<TreeItem>>,
+    // ids of the items expanded on first render
+    pub expanded: Vec<String>,
+    // called with the id of a lazy item expanded while it has no children yet
+    pub on_load: Option<ChangeHandler<String>>,
+    // called with the id of the item selected with Enter or a click
+    pub on_select: Option<ChangeHandler<String>>,
+    pub disabled: bool,
+}
+
+impl Default for TreeViewProps {
+    fn default() -> Self {
+        TreeViewProps {
+            pos: Position {
+                ..Default::default()
+            },
+            dim: Dimension {
+                ..Default::default()
+            },
+            items: Rc::new(Vec::new()),
+            expanded: Vec::new(),
+            on_load: None,
+            on_select: None,
+            disabled: false,
+        }
+    }
+}
+
+// Tree of expandable items with indentation guides, navigated with the keyboard
+// once focused by a click. Only the visible rows are rendered to nodes.
+pub struct TreeView {
+    pub props: TreeViewProps,
+}
+
+#[derive(Clone)]
+struct TreeViewState {
+    expanded: HashSet<String>,
+    // id of the item under the cursor; None before the first move
+    cursor_id: Option<String>,
+    // index of the first visible row
+    offset: usize,
+}
+
+pub fn tree_view(props: TreeViewProps) -> El<TUINode> {
+    El::Component(Box::new(TreeView { props }))
+}
+
+// Item shown on a row of the rendered tree
+struct TreeRow {
+    id: String,
+    label: String,
+    depth: usize,
+    // index of the row of the parent item
+    parent: Option<usize>,
+    expandable: bool,
+    expanded: bool,
+    lazy_empty: bool,
+    // indentation guides drawn before the expand marker
+    guides: String,
+}
+
+// Rows of the expanded items, in display order
+fn flatten(items: &[TreeItem], expanded: &HashSet<String>) -> Vec<TreeRow> {
+    fn walk(
+        items: &[TreeItem],
+        expanded: &HashSet<String>,
+        depth: usize,
+        parent: Option<usize>,
+        prefix: &str,
+        rows: &mut Vec<TreeRow>,
+    ) {
+        for (i, item) in items.iter().enumerate() {
+            let last = i + 1 == items.len();
+            let is_expanded = item.expandable() && expanded.contains(&item.id);
+            // top level items hang from nothing
+            let guides = match depth {
+                0 => String::new(),
+                _ => format!("{}{}", prefix, if last { "└─" } else { "├─" }),
+            };
+
+            let index = rows.len();
+            rows.push(TreeRow {
+                id: item.id.clone(),
+                label: item.label.clone(),
+                depth,
+                parent,
+                expandable: item.expandable(),
+                expanded: is_expanded,
+                lazy_empty: item.lazy && item.children.is_empty(),
+                guides,
+            });
+
+            if is_expanded {
+                let child_prefix = match depth {
+                    0 => String::new(),
+                    _ => format!("{}{}", prefix, if last { "  " } else { "│ " }),
+                };
+                walk(
+                    &item.children,
+                    expanded,
+                    depth + 1,
+                    Some(index),
+                    &child_prefix,
+                    rows,
+                );
+            }
+        }
+    }
+
+    let mut rows = Vec::new();
+    walk(items, expanded, 0, None, "", &mut rows);
+    rows
+}
+
+impl StateReceiver<TreeViewState> for TreeView {}
+impl Component<TUINode> for TreeView {
+    fn initial_state(&self) -> Rc<dyn Any> {
+        Rc::new(TreeViewState {
+            expanded: self.props.expanded.iter().cloned().collect(),
+            cursor_id: None,
+            offset: 0,
+        })
+    }
+
+    fn render(&self, state: Rc<BoxedState>, set_state: Rc<SetState>) -> El<TUINode> {
+        let props = &self.props;
+        let state = self.must_receive_state(state);
+        let height = usize::from(props.dim.height);
+
+        let rows = Rc::new(flatten(&props.items, &state.expanded));
+        // the item under the cursor may have been collapsed or removed
+        let cursor = state
+            .cursor_id
+            .as_ref()
+            .and_then(|id| rows.iter().position(|r| &r.id == id))
+            .unwrap_or(0);
+        let (cursor, offset) = move_cursor(cursor, state.offset, 0, rows.len(), height);
+
+        let ctx = Rc::new(TreeViewContext {
+            state: state.clone(),
+            rows: rows.clone(),
+            cursor,
+            offset,
+            height,
+            set_state,
+            on_load: props.on_load.clone(),
+            on_select: props.on_select.clone(),
+        });
+
+        let nodes = rows
+            .iter()
+            .enumerate()
+            .skip(offset)
+            .take(height)
+            .map(|(i, row)| {
+                let marker = match (row.expandable, row.expanded) {
+                    (true, true) => "▾ ",
+                    (true, false) => "▸ ",
+                    (false, _) => "  ",
+                };
+                El::Node(Node::new(
+                    TUINode::new(props.pos.left, props.pos.top + (i - offset) as u16)
+                        .set_dimension(props.dim.width, 1)
+                        .set_text(Some(align_text(
+                            &format!("{}{}{}", row.guides, marker, row.label),
+                            props.dim.width.into(),
+                            Align::Left,
+                        )))
+                        .set_inverse(i == cursor)
+                        .set_on_click(Some(handle_on_click(ctx.clone(), i))),
+                ))
+            })
+            .collect();
+
+        El::Node(
+            Node::new(
+                TUINode::new(props.pos.left, props.pos.top)
+                    .set_dimension(props.dim.width, props.dim.height)
+                    .set_clip(true)
+                    .set_focusable(true)
+                    .disable(props.disabled)
+                    .set_on_key(Some(handle_on_key(ctx.clone())))
+                    .set_on_wheel(Some(handle_on_wheel(ctx))),
+            )
+            .add_children(nodes),
+        )
+    }
+}
+
+// What the handlers of a rendered tree need
+struct TreeViewContext {
+    state: TreeViewState,
+    rows: Rc<Vec<TreeRow>>,
+    // index of the row under the cursor
+    cursor: usize,
+    offset: usize,
+    height: usize,
+    set_state: Rc<SetState>,
+    on_load: Option<ChangeHandler<String>>,
+    on_select: Option<ChangeHandler<String>>,
+}
+
+impl TreeViewContext {
+    fn move_by(&self, delta: isize) -> TreeViewState {
+        let (cursor, offset) = move_cursor(
+            self.cursor,
+            self.offset,
+            delta,
+            self.rows.len(),
+            self.height,
+        );
+        TreeViewState {
+            cursor_id: self.rows.get(cursor).map(|r| r.id.clone()),
+            offset,
+            ..self.state.clone()
+        }
+    }
+
+    fn move_to(&self, row: usize) -> TreeViewState {
+        self.move_by(row as isize - self.cursor as isize)
+    }
+
+    fn commit(&self, state: TreeViewState) {
+        (self.set_state)(Rc::new(state));
+    }
+
+    // Expands or collapses the item of a row, loading its children if needed
+    fn set_expanded(&self, index: usize, expanded: bool) -> Option<Message> {
+        let row = self.rows.get(index)?;
+        if !row.expandable || row.expanded == expanded {
+            return None;
+        }
+
+        let mut state = self.state.clone();
+        if expanded {
+            state.expanded.insert(row.id.clone());
+        } else {
+            state.expanded.remove(&row.id);
+            // a cursor hidden by the collapse moves up to the collapsed item
+            if self.is_descendant(self.cursor, index) {
+                state.cursor_id = Some(row.id.clone());
+            }
+        }
+        self.commit(state);
+
+        match (expanded && row.lazy_empty, &self.on_load) {
+            (true, Some(on_load)) => on_load(row.id.clone()),
+            _ => None,
+        }
+    }
+
+    fn is_descendant(&self, row: usize, ancestor: usize) -> bool {
+        let mut parent = self.rows[row].parent;
+        while let Some(p) = parent {
+            if p == ancestor {
+                return true;
+            }
+            parent = self.rows[p].parent;
+        }
+        false
+    }
+
+    fn select(&self, row: usize) -> Option<Message> {
+        let id = self.rows.get(row)?.id.clone();
+        self.commit(self.move_to(row));
+        self.on_select.as_ref().and_then(|h| h(id))
+    }
+}
+
+fn handle_on_click(ctx: Rc<TreeViewContext>, row: usize) -> MouseHandler {
+    Rc::new(move |event: &mut UIEvent| {
+        let r = &ctx.rows[row];
+        let marker_left = r.guides.chars().count() as i32;
+        // a click on the expand marker toggles instead of selecting
+        if r.expandable && event.left >= marker_left && event.left < marker_left + 2 {
+            return ctx.set_expanded(row, !r.expanded);
+        }
+        ctx.select(row)
+    })
+}
+
+fn handle_on_wheel(ctx: Rc<TreeViewContext>) -> MouseHandler {
+    Rc::new(move |event: &mut UIEvent| {
+        let delta = match event.action {
+            MouseAction::Wheel(WheelDirection::Up) => -1,
+            MouseAction::Wheel(WheelDirection::Down) => 1,
+            _ => return None,
+        };
+        event.stop_propagation();
+        ctx.commit(ctx.move_by(delta));
+        None
+    })
+}
+
+fn handle_on_key(ctx: Rc<TreeViewContext>) -> KeyHandler {
+    Rc::new(move |event: &mut KeyEvent| {
+        let page = ctx.height.max(1) as isize;
+        let last = ctx.rows.len() as isize;
+        let row = ctx.rows.get(ctx.cursor)?;
+        let delta = match event.key {
+            Key::Up => -1,
+            Key::Down => 1,
+            Key::PageUp => -page,
+            Key::PageDown => page,
+            Key::Home => -last,
+            Key::End => last,
+            // expands, or goes to the first child of an expanded item
+            Key::Right if row.expandable && !row.expanded => {
+                event.stop_propagation();
+                return ctx.set_expanded(ctx.cursor, true);
+            }
+            Key::Right => match ctx.rows.get(ctx.cursor + 1) {
+                Some(next) if next.depth > row.depth => 1,
+                _ => return None,
+            },
+            // collapses, or goes to the parent of a collapsed item
+            Key::Left if row.expanded => {
+                event.stop_propagation();
+                return ctx.set_expanded(ctx.cursor, false);
+            }
+            Key::Left => match row.parent {
+                Some(parent) => parent as isize - ctx.cursor as isize,
+                None => return None,
+            },
+            Key::Char(' ') => {
+                event.stop_propagation();
+                return ctx.set_expanded(ctx.cursor, !row.expanded);
+            }
+            Key::Char('\n') => {
+                event.stop_propagation();
+                return ctx.select(ctx.cursor);
+            }
+            _ => return None,
+        };
+        event.stop_propagation();
+        ctx.commit(ctx.move_by(delta));
+        None
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+    use crate::frontend::tui::widgets::testing::Harness;
+
+    fn items(veggies: Vec<TreeItem>) -> Rc<Vec<TreeItem>> {
+        Rc::new(vec![
+            TreeItem::new("fruits", "fruits").add_children(vec![
+                TreeItem::new("apple", "apple"),
+                TreeItem::new("berries", "berries").add_child(TreeItem::new("straw", "straw")),
+            ]),
+            TreeItem::new("veggies", "veggies")
+                .set_lazy(true)
+                .add_children(veggies),
+        ])
+    }
+
+    // The items can be replaced, e.g. once loaded
+    fn harness(items: Rc<RefCell<Rc<Vec<TreeItem>>>>) -> Harness {
+        let mut h = Harness::new(move || {
+            tree_view(TreeViewProps {
+                dim: Dimension {
+                    width: 16,
+                    height: 6,
+                },
+                items: items.borrow().clone(),
+                on_load: Some(Rc::new(|id: String| message(id))),
+                ..Default::default()
+            })
+        });
+        // focused by a click on a label, which selects the first item
+        h.click(4, 0);
+        h
+    }
+
+    fn rows(h: &Harness) -> Vec<String> {
+        h.texts().iter().map(|t| t.trim_end().to_string()).collect()
+    }
+
+    // Row under the cursor, drawn inversed
+    fn cursor(h: &Harness) -> Option<String> {
+        fn find(e: &RenderedEl<TUINode>) -> Option<String> {
+            match e {
+                RenderedEl::None => None,
+                RenderedEl::Container(cont) => cont.iter().find_map(find),
+                RenderedEl::Node(node) if node.payload.style.inverse => node.payload.text.clone(),
+                RenderedEl::Node(node) => node.children.iter().find_map(find),
+            }
+        }
+        find(h.graph.as_ref()?).map(|t| t.trim().to_string())
+    }
+
+    #[test]
+    fn markers_expand_and_collapse() {
+        let mut h = harness(Rc::new(RefCell::new(items(Vec::new()))));
+        assert_eq!(rows(&h), ["▸ fruits", "▸ veggies"]);
+
+        h.click(0, 0);
+        assert_eq!(
+            rows(&h),
+            ["▾ fruits", "├─  apple", "└─▸ berries", "▸ veggies"]
+        );
+        h.click(2, 2);
+        assert_eq!(
+            rows(&h),
+            [
+                "▾ fruits",
+                "├─  apple",
+                "└─▾ berries",
+                "  └─  straw",
+                "▸ veggies"
+            ]
+        );
+        h.click(1, 0);
+        assert_eq!(rows(&h), ["▸ fruits", "▸ veggies"]);
+    }
+
+    #[test]
+    fn arrows_walk_the_tree() {
+        let mut h = harness(Rc::new(RefCell::new(items(Vec::new()))));
+        let mut walk = |key: Key| {
+            h.press(key);
+            cursor(&h).unwrap()
+        };
+        // expands, then goes to the first child
+        assert_eq!(walk(Key::Right), "▾ fruits");
+        assert_eq!(walk(Key::Right), "├─  apple");
+        assert_eq!(walk(Key::Down), "└─▸ berries");
+        assert_eq!(walk(Key::Right), "└─▾ berries");
+        assert_eq!(walk(Key::Right), "└─  straw");
+        // goes to the parent, then collapses
+        assert_eq!(walk(Key::Left), "└─▾ berries");
+        assert_eq!(walk(Key::Left), "└─▸ berries");
+        assert_eq!(walk(Key::Up), "├─  apple");
+        assert_eq!(walk(Key::Left), "▾ fruits");
+        assert_eq!(walk(Key::Left), "▸ fruits");
+        assert_eq!(walk(Key::Down), "▸ veggies");
+        assert_eq!(walk(Key::Down), "▸ veggies");
+    }
+
+    #[test]
+    fn collapsing_moves_the_cursor_out() {
+        let mut h = harness(Rc::new(RefCell::new(items(Vec::new()))));
+        h.press(Key::Right);
+        h.press(Key::End);
+        assert_eq!(cursor(&h).unwrap(), "▸ veggies");
+        h.press(Key::Up);
+        h.click(0, 0);
+        assert_eq!(cursor(&h).unwrap(), "▸ fruits");
+    }
+
+    #[test]
+    fn lazy_items_are_loaded_once_expanded() {
+        let items_cell = Rc::new(RefCell::new(items(Vec::new())));
+        let mut h = harness(items_cell.clone());
+        h.press(Key::Down);
+        h.press(Key::Right);
+        assert_eq!(h.input.take_messages::<String>(), ["veggies"]);
+        // nothing to show yet
+        assert_eq!(rows(&h), ["▸ fruits", "▾ veggies"]);
+
+        *items_cell.borrow_mut() = items(vec![TreeItem::new("carrot", "carrot")]);
+        h.render();
+        assert_eq!(rows(&h), ["▸ fruits", "▾ veggies", "└─  carrot"]);
+
+        // loaded items are not loaded again
+        h.press(Key::Left);
+        h.press(Key::Right);
+        assert!(h.input.take_messages::<String>().is_empty());
+    }
+}